@@ -2,7 +2,10 @@ pub const PROTOCOL_FEE_BPS: u128 = 500; // 5%
 pub const FINAL_PRIZE_BPS: u128 = 1_000; // 10%
 pub const BPS_DENOMINATOR: u128 = 10_000;
 pub const BET_CLOSE_WINDOW_SECONDS: u64 = 600; // 10 minutes
+/// Default final prize curve; replaceable with `set_prize_curve` before the first bet.
 pub const FINAL_PRIZE_TOP5_BPS: [u128; 5] = [4_500, 2_500, 1_500, 1_000, 500];
+/// Longest payout table accepted by `set_prize_curve`.
+pub const MAX_PRIZE_POSITIONS: usize = 100;
 
 // ── Security constants ────────────────────────────────────────────────────────
 
@@ -18,8 +21,63 @@ pub const MAX_POINTS_WEIGHT: u32 = 20;
 /// Maximum byte length for team and pick names to prevent storage bloat and gas DoS.
 pub const MAX_TEAM_NAME_LEN: usize = 50;
 
-/// Optimistic execution: window during which admin can cancel an oracle proposal.
-pub const CHALLENGE_WINDOW_MS: u64 = 120_000; //2 minutos // 86_400_000; = 24 hours // 4 hours(Final)
+/// Optimistic execution: default window during which admin can cancel an oracle proposal.
+/// Overridable per phase via `set_phase_windows` (e.g. 24 hours normally, 4 hours for the Final).
+pub const DEFAULT_CHALLENGE_WINDOW_MS: u64 = 120_000; //2 minutos // 86_400_000; = 24 hours
+pub const MIN_CHALLENGE_WINDOW_MS: u64 = 60_000; // 1 minute
+pub const MAX_CHALLENGE_WINDOW_MS: u64 = 172_800_000; // 48 hours
 
-/// Claim window: time winners have to claim their match reward after finalization.
-pub const CLAIM_DEADLINE_MS: u64 = 240_000; //4 minutos // 259_200_000;= 72 hours // 48 hours(Final)
+/// Default claim window: time winners have to claim their match reward after finalization.
+/// Overridable per phase via `set_phase_windows` (e.g. 72 hours normally, 48 hours for the Final).
+pub const DEFAULT_CLAIM_DEADLINE_MS: u64 = 240_000; //4 minutos // 259_200_000;= 72 hours
+pub const MIN_CLAIM_DEADLINE_MS: u64 = 60_000; // 1 minute
+pub const MAX_CLAIM_DEADLINE_MS: u64 = 604_800_000; // 7 days
+
+/// Resolution deadline: a match with no finalized result this long after kick-off can be expired
+/// and its bettors refunded. Configurable by admin within the bounds below.
+pub const DEFAULT_RESOLUTION_PERIOD_MS: u64 = 604_800_000; // 7 days
+pub const MIN_RESOLUTION_PERIOD_MS: u64 = 3_600_000; // 1 hour
+pub const MAX_RESOLUTION_PERIOD_MS: u64 = 2_592_000_000; // 30 days
+
+/// Bond a bettor posts to dispute a proposed result: refunded if upheld, sent to the final prize if rejected.
+pub const DISPUTE_BOND_PLANCK: u128 = 10_000_000_000_000; // 10 VARA
+
+/// Default share of the protocol fee on referred users' bets that accrues to their referrer (20%).
+pub const DEFAULT_REFERRAL_FEE_BPS: u128 = 2_000;
+
+/// Most free predictions one match accepts, bounding its free-to-play settlement.
+pub const MAX_FREE_PREDICTIONS_PER_MATCH: usize = 5_000;
+
+/// Upper bound for the exact-score jackpot cut, in BPS of each bet (5%).
+pub const MAX_JACKPOT_BPS: u128 = 500;
+
+/// Leg count bounds for a parlay.
+pub const MIN_PARLAY_LEGS: usize = 2;
+pub const MAX_PARLAY_LEGS: usize = 8;
+
+/// Largest market line accepted by `create_market`, in hundredths of a goal.
+pub const MAX_MARKET_LINE: i32 = 2_000;
+
+/// Bonus points for predicting a group's winner and runner-up.
+pub const GROUP_FIRST_PLACE_POINTS: u32 = 5;
+pub const GROUP_SECOND_PLACE_POINTS: u32 = 3;
+
+/// Most matches a group can hold.
+pub const MAX_GROUP_MATCHES: usize = 12;
+
+/// Jokers per wallet per phase and the points multiplier they apply, until changed.
+pub const DEFAULT_JOKERS_PER_PHASE: u32 = 1;
+pub const DEFAULT_JOKER_MULTIPLIER: u32 = 2;
+pub const MAX_JOKER_MULTIPLIER: u32 = 3;
+
+/// Times an exact-score winner's stake counts in the match reward split, until changed.
+pub const DEFAULT_EXACT_SCORE_WEIGHT: u32 = 1;
+pub const MAX_EXACT_SCORE_WEIGHT: u32 = 5;
+
+/// Bounds for streak and early-bird bonuses: threshold count and points per bonus.
+pub const MAX_STREAK_THRESHOLDS: usize = 5;
+pub const MAX_BONUS_POINTS: u32 = 20;
+
+/// Byte length bounds for referral codes.
+pub const MIN_REFERRAL_CODE_LEN: usize = 3;
+pub const MAX_REFERRAL_CODE_LEN: usize = 32;
@@ -1,5 +1,8 @@
 use sails_rs::prelude::*;
-use super::types::{Score, PenaltyWinner};
+use super::types::{
+    Score, PenaltyWinner, PrizeCurve, ParlayLeg, ParlayStatus, MarketKind, MarketParams,
+    MarketSelection, Prediction,
+};
 
 #[event]
 #[derive(Debug, Encode, Decode, TypeInfo)]
@@ -7,17 +10,22 @@ use super::types::{Score, PenaltyWinner};
 #[scale_info(crate = sails_rs::scale_info)]
 pub enum SmartCupEvent {
     PhaseRegistered(String),
+    PhaseWindowsSet(String, u64, u64), // phase, challenge_window_ms, claim_deadline_ms
     MatchRegistered(u64, String, String, String, u64),
+    PlaceholderMatchRegistered(u64, String, u64, u64, u64), // match_id, phase, feeders, kick_off
+    PlaceholderFilled(u64, String, String), // match_id, home, away (empty = not decided yet)
     OracleAuthorized(ActorId, bool),
     BetAccepted(ActorId, u64, Score, Option<PenaltyWinner>, u128),
     ResultProposed(u64, Score, Option<PenaltyWinner>, ActorId, u64), // last u64 = challenge_expires_at
     ResultFinalized(u64, Score, Option<PenaltyWinner>),
     SettlementPrepared(u64, u128),
+    SettlementProgress(u64, u32, u32), // match_id, processed, total participants
     PointsAwarded(ActorId, u64, u32),
     MatchRewardClaimed(u64, ActorId, u128),
     MatchDustSwept(u64, u128),
     PodiumPickSubmitted(ActorId, String, String, String),
     PodiumFinalized(String, String, String),
+    PodiumMatchesSet(u64, u64), // final, third-place match
     PodiumBonusAwarded(ActorId, u32),
     FinalPrizeSent(u128, ActorId),
     ProtocolFeesWithdrawn(u128, ActorId),
@@ -27,4 +35,72 @@ pub enum SmartCupEvent {
     FinalPrizeClaimed(ActorId, u128),
     FinalPrizeRoundingDustWithdrawn(u128, ActorId),
     ResultProposalCancelled(u64, ActorId),
+    ResolutionPeriodSet(u64),
+    MatchExpired(u64),
+    MatchRefundClaimed(u64, ActorId, u128),
+    DaoProgramSet(Option<ActorId>),
+    ResultDisputed(u64, u32, ActorId, Score, Option<PenaltyWinner>), // match_id, dispute index, disputer
+    DisputeResolved(u64, u32, bool), // match_id, dispute index, upheld
+    ReferralFeeBpsSet(u128),
+    ReferrerRegistered(ActorId, String),
+    ReferrerSet(ActorId, ActorId), // user, referrer
+    ReferralRewardsClaimed(ActorId, u128),
+    PaymentTokenSet(Option<ActorId>),
+    PayoutFailed(ActorId, u128), // recipient, amount — payout parked for retry
+    FailedPayoutRetried(ActorId, u128),
+    PredictionPlaced(ActorId, u64, Score, Option<PenaltyWinner>),
+    FreePointsAwarded(ActorId, u64, u32),
+    FreeSettlementProgress(u64, u32, u32), // match_id, processed, total free predictors
+    SponsorPoolFunded(ActorId, u128),
+    FreePrizePoolFinalized(u128, u128), // allocated, dust
+    FreePrizeClaimed(ActorId, u128),
+    PrizeCurveSet(PrizeCurve),
+    PhasePrizeBpsSet(u128),
+    PhasePrizeFinalized(String, u128, u128), // phase, allocated, dust
+    PhasePrizeClaimed(String, ActorId, u128),
+    PhasePrizeSwept(String, u128), // phase, unclaimed amount
+    JackpotBpsSet(u128),
+    JackpotWon(u64, u128, u128), // match_id, jackpot, exact-hit stake
+    JackpotRolledOver(u64, Option<u64>, u128), // from match, to match (None = final prize), amount
+    JackpotClaimed(u64, ActorId, u128),
+    ParlayPlaced(u64, ActorId, Vec<ParlayLeg>, u128), // parlay_id, owner, legs, stake in pool
+    ParlaySettled(u64, ParlayStatus, u32), // parlay_id, status, live legs
+    ParlayPoolFinalized(u128, u128), // pool, winning weight (0 = pool sent to final prize)
+    ParlayClaimed(u64, ActorId, u128),
+    MarketCreated(u64, u64, MarketKind, MarketParams), // market_id, match_id
+    MarketBetAccepted(u64, ActorId, MarketSelection, u128),
+    MarketSettled(u64, Option<MarketSelection>, u128), // None = void; winner stake
+    MarketRewardClaimed(u64, ActorId, u128),
+    MarketRefundClaimed(u64, ActorId, u128),
+    MarketDustSwept(u64, u128),
+    ChallengeCreated(u64, ActorId, u64, Option<ActorId>, Prediction, u128), // id, creator, match_id, opponent
+    ChallengeAccepted(u64, ActorId, Prediction),
+    ChallengeSettled(u64, Option<ActorId>, u128, u128), // id, winner (None = draw), payout, fee
+    ChallengeRefunded(u64),
+    SurvivorEntryFeeSet(u128),
+    SurvivorRoundRegistered(u32, Vec<u64>),
+    SurvivorJoined(ActorId, u128),
+    SurvivorPicked(ActorId, u32, String),
+    SurvivorEliminated(ActorId, u32),
+    SurvivorRoundSettled(u32, u32), // round_id, entrants still alive
+    SurvivorFinished(u32, u128), // winners, pool
+    SurvivorPrizeClaimed(ActorId, u128),
+    BracketSlotRegistered(u32, Option<u64>, Option<(u32, u32)>),
+    BracketMatchAssigned(u32, u64),
+    BracketSubmitted(ActorId),
+    BracketSlotDecided(u32, String, u32), // slot_id, advancing team, points
+    BracketSlotVoided(u32),
+    BracketPointsAwarded(ActorId, u32, u32), // user, slot_id, points
+    GroupRegistered(String, Vec<u64>),
+    GroupPickSubmitted(ActorId, String, String, String), // user, group, first, second
+    GroupFinalized(String, Vec<String>), // group, teams in table order
+    GroupVoided(String),
+    GroupBonusAwarded(ActorId, String, u32),
+    JokerConfigSet(u32, u32), // jokers per phase, multiplier
+    JokerApplied(ActorId, u64, u32), // user, match_id, multiplier
+    StreakBonusesSet(Vec<(u32, u32)>), // (run length, points)
+    EarlyBirdBonusSet(u64, u32), // lead before kick-off in ms, points
+    PhaseBonusAwarded(ActorId, String, u32, u32), // user, phase, streak, early bird
+    PhaseBonusesSettled(String),
+    ExactScoreWeightSet(u32),
 }
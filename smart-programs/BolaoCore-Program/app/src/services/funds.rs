@@ -0,0 +1,109 @@
+use sails_rs::{prelude::*, gstd::{msg, exec}};
+use super::state::SmartCupState;
+
+/// Route of the standard fungible-token service on the VFT program.
+const VFT_SERVICE: &str = "Vft";
+
+/// Sends an encoded VFT call and decodes its `bool` reply.
+/// Any send error, reply error or undecodable reply counts as a failed transfer.
+async fn call_vft(token: ActorId, payload: Vec<u8>) -> bool {
+    let future = match msg::send_bytes_for_reply(token, &payload, 0, 0) {
+        Ok(future) => future,
+        Err(_) => return false,
+    };
+    match future.await {
+        Ok(bytes) => <(String, String, bool)>::decode(&mut bytes.as_slice())
+            .map(|(_, _, ok)| ok)
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/// Moves `amount` of `token` from `from` to `to` using the allowance `from` granted this program.
+pub async fn transfer_from(token: ActorId, from: ActorId, to: ActorId, amount: u128) -> bool {
+    let payload = (VFT_SERVICE, "TransferFrom", from, to, U256::from(amount)).encode();
+    call_vft(token, payload).await
+}
+
+/// Moves `amount` of `token` from this program's balance to `to`.
+pub async fn transfer(token: ActorId, to: ActorId, amount: u128) -> bool {
+    let payload = (VFT_SERVICE, "Transfer", to, U256::from(amount)).encode();
+    call_vft(token, payload).await
+}
+
+/// Pays `amount` to `to` in the deployment's currency (native VARA or the configured token).
+/// Returns false if the transfer failed; callers that keep their state committed go
+/// through `pay_or_park` instead.
+pub async fn pay(to: ActorId, amount: u128) -> bool {
+    match SmartCupState::state_ref().payment_token {
+        Some(token) => transfer(token, to, amount).await,
+        None => msg::send_with_gas(to, (), 0, amount).is_ok(),
+    }
+}
+
+/// Takes a fixed `amount` from the caller in the deployment's currency.
+/// Native mode requires the attached value to match; token mode pulls it via `transfer_from`
+/// and rejects any attached value. Returns false if the token transfer failed.
+pub async fn collect(from: ActorId, amount: u128) -> bool {
+    match SmartCupState::state_ref().payment_token {
+        Some(token) => {
+            if msg::value() != 0 {
+                panic!("Native value not accepted in token mode");
+            }
+            transfer_from(token, from, exec::program_id(), amount).await
+        }
+        None => {
+            if msg::value() != amount {
+                panic!("Attached value does not match amount");
+            }
+            true
+        }
+    }
+}
+
+/// Takes `amount` from `payer` like `collect`, then re-runs `recheck`, since the state may
+/// have moved on while awaiting the token. A rejected payment is refunded and the call
+/// panics with the rejection reason; if the refund fails too it is parked for
+/// `retry_failed_payout` and the reason is returned instead, so the caller can report
+/// the parked refund and return without rolling the park back.
+pub async fn collect_checked<F>(
+    payer: ActorId,
+    amount: u128,
+    recheck: F,
+) -> Result<(), &'static str>
+where
+    F: FnOnce() -> Result<(), &'static str>,
+{
+    if !collect(payer, amount).await {
+        panic!("Token transfer failed");
+    }
+    if let Err(reason) = recheck() {
+        if pay_or_park(vec![(payer, amount)]).await.is_empty() {
+            panic!("{}", reason);
+        }
+        return Err(reason);
+    }
+    Ok(())
+}
+
+/// Pays out a claim, prize, refund or withdrawal whose bookkeeping the caller has already
+/// committed (checks-effects-interactions). A failed transfer never rolls that back: it
+/// is parked in `failed_payouts` for `retry_failed_payout`, and this returns false so the
+/// caller can report it.
+pub async fn pay_out(to: ActorId, amount: u128) -> bool {
+    pay_or_park(vec![(to, amount)]).await.is_empty()
+}
+
+/// Pays each `(recipient, amount)` in turn. Failed payouts are parked in `failed_payouts`
+/// so the recipient can retry them with `retry_failed_payout`. Returns the parked ones.
+pub async fn pay_or_park(payouts: Vec<(ActorId, u128)>) -> Vec<(ActorId, u128)> {
+    let mut parked = Vec::new();
+    for (to, amount) in payouts {
+        if !pay(to, amount).await {
+            let owed = SmartCupState::state_mut().failed_payouts.entry(to).or_insert(0);
+            *owed = owed.saturating_add(amount);
+            parked.push((to, amount));
+        }
+    }
+    parked
+}
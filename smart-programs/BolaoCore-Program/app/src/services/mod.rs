@@ -3,4 +3,5 @@ pub mod types;
 pub mod events;
 pub mod state;
 pub mod utils;
+pub mod funds;
 pub mod service;
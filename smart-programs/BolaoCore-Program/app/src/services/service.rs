@@ -1,21 +1,35 @@
 use sails_rs::{prelude::*, gstd::{exec, msg}};
 
 use super::constants::{
-    PROTOCOL_FEE_BPS, FINAL_PRIZE_BPS, BPS_DENOMINATOR,
-    BET_CLOSE_WINDOW_SECONDS, MIN_BET_PLANCK,
+    BPS_DENOMINATOR, FINAL_PRIZE_BPS,
     MAX_PHASE_NAME_LEN, MAX_POINTS_WEIGHT, MAX_TEAM_NAME_LEN,
-    CHALLENGE_WINDOW_MS, CLAIM_DEADLINE_MS,
+    DEFAULT_CHALLENGE_WINDOW_MS, DEFAULT_CLAIM_DEADLINE_MS,
+    MIN_CHALLENGE_WINDOW_MS, MAX_CHALLENGE_WINDOW_MS,
+    MIN_CLAIM_DEADLINE_MS, MAX_CLAIM_DEADLINE_MS,
+    MIN_RESOLUTION_PERIOD_MS, MAX_RESOLUTION_PERIOD_MS, DISPUTE_BOND_PLANCK,
+    MIN_REFERRAL_CODE_LEN, MAX_REFERRAL_CODE_LEN, MAX_PRIZE_POSITIONS, MAX_JACKPOT_BPS,
+    BET_CLOSE_WINDOW_SECONDS, MAX_MARKET_LINE, MIN_BET_PLANCK,
+    MAX_JOKER_MULTIPLIER, MAX_STREAK_THRESHOLDS, MAX_BONUS_POINTS, MAX_EXACT_SCORE_WEIGHT,
+    MAX_FREE_PREDICTIONS_PER_MATCH,
 };
 use super::types::{
-    Score, PenaltyWinner, ResultStatus, Match, Bet, UserBetRecord,
+    Score, PenaltyWinner, ResultStatus, Match,
     UserBetView, PhaseConfig, PodiumPick, PodiumResult,
-    WalletClaimStatus, FinalPrizeClaimStatus,
+    WalletClaimStatus, FinalPrizeClaimStatus, Dispute, DisputeStatus, ReferralStats,
+    FreePrediction, UserStats, BetHistoryEntry, BetOutcome, SolvencyReport, PrizeCurve,
+    JackpotStatus, Parlay, ParlayLeg, ParlayStatus, Market, MarketBet, MarketKind,
+    MarketParams, MarketSelection, MarketStatus, Challenge, ChallengeStatus, Prediction,
+    SurvivorRound, SurvivorEntry, SurvivorStatus, BracketSlot, BracketEntry, Group, GroupPick,
+    GroupStanding,
 };
 use super::events::SmartCupEvent;
 use super::state::{SmartCupState, IoSmartCupState};
+use super::funds;
 use super::utils::{
-    outcome, advance_outcome, is_knockout, eligible_for_payout,
-    top5_share_sum_bps, collect_leaderboard,
+    is_knockout, eligible_for_payout, payout_side, collect_leaderboard,
+    prediction_points, prediction_outcome,
+    collect_free_leaderboard, collect_phase_leaderboard, allocate_by_rank, jackpot_share,
+    market_selections, market_winner, group_table, weighted_winner_stake,
 };
 
 // ── Service bootstrap ─────────────────────────────────────────────────────────
@@ -81,6 +95,8 @@ impl Service {
             start_time,
             end_time,
             points_weight,
+            challenge_window_ms: DEFAULT_CHALLENGE_WINDOW_MS,
+            claim_deadline_ms: DEFAULT_CLAIM_DEADLINE_MS,
         };
         state.phases.insert(phase_name.clone(), phase);
 
@@ -88,6 +104,45 @@ impl Service {
             .expect("event");
     }
 
+    /// Overrides the challenge window and claim deadline of a phase (e.g. shorter for the Final).
+    /// Only before the phase's first match is registered.
+    #[export]
+    pub fn set_phase_windows(
+        &mut self,
+        phase_name: String,
+        challenge_window_ms: u64,
+        claim_deadline_ms: u64,
+    ) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !(MIN_CHALLENGE_WINDOW_MS..=MAX_CHALLENGE_WINDOW_MS).contains(&challenge_window_ms) {
+            panic!("Challenge window out of bounds");
+        }
+        if !(MIN_CLAIM_DEADLINE_MS..=MAX_CLAIM_DEADLINE_MS).contains(&claim_deadline_ms) {
+            panic!("Claim deadline out of bounds");
+        }
+
+        if !state.phases.contains_key(&phase_name) {
+            panic!("Phase not found");
+        }
+        // Windows already in force on a match must not move under its bettors
+        if state.matches.values().any(|m| m.phase == phase_name) {
+            panic!("Phase windows locked once the phase has matches");
+        }
+
+        let phase = state.phases.get_mut(&phase_name).expect("Phase not found");
+        phase.challenge_window_ms = challenge_window_ms;
+        phase.claim_deadline_ms = claim_deadline_ms;
+
+        self.emit_event(SmartCupEvent::PhaseWindowsSet(
+            phase_name,
+            challenge_window_ms,
+            claim_deadline_ms,
+        ))
+        .expect("event");
+    }
+
     #[export]
     pub fn register_match(
         &mut self,
@@ -114,38 +169,7 @@ impl Service {
             panic!("kick_off must be in the future");
         }
 
-        let match_id = state.next_match_id.saturating_add(1);
-        state.next_match_id = match_id;
-
-        if phase == "Round of 32" {
-            match state.r32_lock_time {
-                None => state.r32_lock_time = Some(kick_off),
-                Some(t) => {
-                    if kick_off < t {
-                        state.r32_lock_time = Some(kick_off);
-                    }
-                }
-            }
-        }
-
-        let m = Match {
-            match_id,
-            phase: phase.clone(),
-            home: home.clone(),
-            away: away.clone(),
-            kick_off,
-            result: ResultStatus::Unresolved,
-            match_prize_pool: 0,
-            has_bets: false,
-            participants: Vec::new(),
-            total_winner_stake: 0,
-            total_claimed: 0,
-            settlement_prepared: false,
-            dust_swept: false,
-            finalized_at: None,
-        };
-
-        state.matches.insert(match_id, m);
+        let match_id = state.insert_match(phase.clone(), home.clone(), away.clone(), kick_off, None);
 
         self.emit_event(SmartCupEvent::MatchRegistered(
             match_id,
@@ -157,6 +181,98 @@ impl Service {
         .expect("event");
     }
 
+    /// Registers a knockout match between the winners of two earlier knockout matches.
+    /// Each team is filled in when its feeder finalizes; betting opens once both are known.
+    #[export]
+    pub fn register_placeholder_match(
+        &mut self,
+        phase: String,
+        home_from: u64,
+        away_from: u64,
+        kick_off: u64,
+    ) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        let phase_weight = state
+            .phases
+            .get(&phase)
+            .map(|p| p.points_weight)
+            .expect("Phase not found");
+        if !is_knockout(phase_weight) {
+            panic!("Placeholder matches must be knockout matches");
+        }
+        if kick_off <= exec::block_timestamp() {
+            panic!("kick_off must be in the future");
+        }
+        if let Err(reason) = state.check_feeders((home_from, away_from), kick_off) {
+            panic!("{}", reason);
+        }
+
+        let match_id = state.insert_match(
+            phase.clone(),
+            String::new(),
+            String::new(),
+            kick_off,
+            Some((home_from, away_from)),
+        );
+        // Feeders already finalized fill their side right away
+        let mut filled = false;
+        for feeder_id in [home_from, away_from] {
+            filled |= state.advance_winner(feeder_id).is_some();
+        }
+
+        self.emit_event(SmartCupEvent::PlaceholderMatchRegistered(
+            match_id, phase, home_from, away_from, kick_off,
+        ))
+        .expect("event");
+        if filled {
+            let m = state.matches.get(&match_id).expect("No such match");
+            self.emit_event(SmartCupEvent::PlaceholderFilled(
+                match_id,
+                m.home.clone(),
+                m.away.clone(),
+            ))
+            .expect("event");
+        }
+    }
+
+    /// Sets the period after kick-off that newly registered matches get as their
+    /// resolution deadline. Already registered matches keep their deadline.
+    #[export]
+    pub fn set_resolution_period(&mut self, period_ms: u64) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !(MIN_RESOLUTION_PERIOD_MS..=MAX_RESOLUTION_PERIOD_MS).contains(&period_ms) {
+            panic!("Resolution period out of bounds");
+        }
+
+        state.resolution_period_ms = period_ms;
+
+        self.emit_event(SmartCupEvent::ResolutionPeriodSet(period_ms))
+            .expect("event");
+    }
+
+    // ── Payment mode ──────────────────────────────────────────────────────────
+
+    /// Selects the currency of the deployment: `Some(vft_program)` for a fungible token,
+    /// `None` for native VARA. Locked once the first bet has been placed.
+    #[export]
+    pub fn set_payment_token(&mut self, token: Option<ActorId>) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.bets.is_empty() {
+            panic!("Payment mode locked after first bet");
+        }
+
+        state.payment_token = token;
+
+        self.emit_event(SmartCupEvent::PaymentTokenSet(token))
+            .expect("event");
+    }
+
     // ── Betting ───────────────────────────────────────────────────────────────
 
     #[export]
@@ -172,82 +288,110 @@ impl Service {
         let sent_value = msg::value();
         let now = exec::block_timestamp();
 
-        let m = state.matches.get_mut(&match_id).expect("Match not found");
-
-        if sent_value < MIN_BET_PLANCK {
-            panic!("Bet below minimum");
+        if state.payment_token.is_some() {
+            panic!("Native bets disabled in token mode");
         }
-
-        let close_time = m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS);
-        if now >= close_time {
-            panic!("Betting closed");
-        }
-        if state.bets.contains_key(&(bettor, match_id)) {
-            panic!("Already bet");
+        if let Err(reason) = state.check_bet(
+            bettor,
+            match_id,
+            predicted_score,
+            predicted_penalty_winner,
+            sent_value,
+            now,
+        ) {
+            panic!("{}", reason);
         }
-        if predicted_score.home > 20 || predicted_score.away > 20 {
-            panic!("Score too high");
+
+        let match_pool_cut = state.record_bet(
+            bettor,
+            match_id,
+            predicted_score,
+            predicted_penalty_winner,
+            sent_value,
+            now,
+        );
+
+        let credited = state.sync_user_points(bettor);
+        for (credited_match, added_points) in credited {
+            self.emit_event(SmartCupEvent::PointsAwarded(bettor, credited_match, added_points))
+                .expect("event");
         }
 
-        let phase_weight = state
-            .phases
-            .get(&m.phase)
-            .map(|p| p.points_weight)
-            .unwrap_or(1);
-        let knockout = is_knockout(phase_weight);
+        self.emit_event(SmartCupEvent::BetAccepted(
+            bettor,
+            match_id,
+            predicted_score,
+            predicted_penalty_winner,
+            match_pool_cut,
+        ))
+        .expect("event");
+    }
 
-        let predicted_draw = predicted_score.home == predicted_score.away;
+    /// Places a bet of `amount` in the configured fungible token. The bettor must have
+    /// approved this program for at least `amount` on the token program beforehand.
+    #[export]
+    pub async fn place_bet_with_token(
+        &mut self,
+        match_id: u64,
+        predicted_score: Score,
+        predicted_penalty_winner: Option<PenaltyWinner>,
+        amount: u128,
+    ) {
+        let bettor = msg::source();
 
-        if !knockout {
-            if predicted_penalty_winner.is_some() {
-                panic!("Penalty winner not allowed in group stage");
+        {
+            let state = SmartCupState::state_ref();
+            if state.payment_token.is_none() {
+                panic!("Token betting not enabled");
             }
-        } else {
-            if predicted_draw {
-                if predicted_penalty_winner.is_none() {
-                    panic!("Knockout draw requires penalty winner");
-                }
-            } else {
-                if predicted_penalty_winner.is_some() {
-                    panic!("Penalty winner only allowed when predicting draw");
-                }
+            if msg::value() != 0 {
+                panic!("Native value not accepted in token mode");
+            }
+            if let Err(reason) = state.check_bet(
+                bettor,
+                match_id,
+                predicted_score,
+                predicted_penalty_winner,
+                amount,
+                exec::block_timestamp(),
+            ) {
+                panic!("{}", reason);
             }
         }
 
-        let protocol_fee = sent_value.saturating_mul(PROTOCOL_FEE_BPS) / BPS_DENOMINATOR;
-        let final_prize_cut = sent_value.saturating_mul(FINAL_PRIZE_BPS) / BPS_DENOMINATOR;
-        let match_pool_cut = sent_value
-            .saturating_sub(protocol_fee)
-            .saturating_sub(final_prize_cut);
-
-        state.protocol_fee_accumulated =
-            state.protocol_fee_accumulated.saturating_add(protocol_fee);
-        state.final_prize_accumulated =
-            state.final_prize_accumulated.saturating_add(final_prize_cut);
-
-        m.match_prize_pool = m.match_prize_pool.saturating_add(match_pool_cut);
-        m.has_bets = true;
-        if !m.participants.contains(&bettor) {
-            m.participants.push(bettor);
+        // Re-validate: betting may have closed or another bet landed while awaiting the token
+        let recheck = || {
+            SmartCupState::state_ref().check_bet(
+                bettor,
+                match_id,
+                predicted_score,
+                predicted_penalty_winner,
+                amount,
+                exec::block_timestamp(),
+            )
+        };
+        if funds::collect_checked(bettor, amount, recheck).await.is_err() {
+            self.emit_event(SmartCupEvent::PayoutFailed(bettor, amount))
+                .expect("event");
+            return;
         }
 
-        let bet = Bet {
-            user: bettor,
-            match_id,
-            score: predicted_score,
-            penalty_winner: predicted_penalty_winner,
-            stake_in_match_pool: match_pool_cut,
-            claimed: false,
-        };
-        state.bets.insert((bettor, match_id), bet);
+        let state = SmartCupState::state_mut();
 
-        let list = state.user_bets.entry(bettor).or_insert(Vec::new());
-        list.push(UserBetRecord {
+        let match_pool_cut = state.record_bet(
+            bettor,
             match_id,
-            score: predicted_score,
-            penalty_winner: predicted_penalty_winner,
-            stake_in_match_pool: match_pool_cut,
-        });
+            predicted_score,
+            predicted_penalty_winner,
+            amount,
+            exec::block_timestamp(),
+        );
+
+        let credited = state.sync_user_points(bettor);
+        for (credited_match, added_points) in credited {
+            self.emit_event(SmartCupEvent::PointsAwarded(bettor, credited_match, added_points))
+                .expect("event");
+        }
 
         self.emit_event(SmartCupEvent::BetAccepted(
             bettor,
@@ -259,100 +403,1733 @@ impl Service {
         .expect("event");
     }
 
-    // ── Oracle: result proposal ───────────────────────────────────────────────
+    // ── Jokers ────────────────────────────────────────────────────────────────
 
+    /// Sets how many jokers each wallet gets per phase and the points multiplier a joker
+    /// applies. Locked once the first bet is placed.
     #[export]
-    pub fn propose_result(
-        &mut self,
-        match_id: u64,
-        final_score: Score,
-        penalty_winner: Option<PenaltyWinner>,
-    ) {
+    pub fn set_joker_config(&mut self, jokers_per_phase: u32, multiplier: u32) {
         let state = SmartCupState::state_mut();
-        state.only_oracle();
+        state.only_admin();
 
-        let oracle = msg::source();
-        let proposed_at = exec::block_timestamp();
-        let m = state.matches.get_mut(&match_id).expect("No such match");
+        if !state.bets.is_empty() {
+            panic!("Joker config locked after first bet");
+        }
+        if !(2..=MAX_JOKER_MULTIPLIER).contains(&multiplier) {
+            panic!("Invalid joker multiplier");
+        }
 
-        match &m.result {
-            ResultStatus::Unresolved => {
-                m.result = ResultStatus::Proposed {
-                    score: final_score,
-                    penalty_winner,
-                    oracle,
-                    proposed_at,
-                };
+        state.jokers_per_phase = jokers_per_phase;
+        state.joker_multiplier = multiplier;
+
+        self.emit_event(SmartCupEvent::JokerConfigSet(jokers_per_phase, multiplier))
+            .expect("event");
+    }
+
+    /// Plays one of the caller's jokers for the match's phase on their bet, multiplying
+    /// the points it earns. Only before betting closes.
+    #[export]
+    pub fn apply_joker(&mut self, match_id: u64) {
+        let state = SmartCupState::state_mut();
+        let user = msg::source();
+
+        let m = state.matches.get(&match_id).expect("No such match");
+        if exec::block_timestamp() >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS) {
+            panic!("Betting closed");
+        }
+        let phase = m.phase.clone();
+        if state.jokers_remaining(user, &phase) == 0 {
+            panic!("No jokers left in this phase");
+        }
+        let multiplier = state.joker_multiplier;
+        let bet = state.bets.get_mut(&(user, match_id)).expect("No bet on this match");
+        if bet.joker_multiplier > 1 {
+            panic!("Joker already applied");
+        }
+        bet.joker_multiplier = multiplier;
+
+        let used = state.jokers_used.entry((user, phase)).or_insert(0);
+        *used = used.saturating_add(1);
+
+        self.emit_event(SmartCupEvent::JokerApplied(user, match_id, multiplier))
+            .expect("event");
+    }
+
+    // ── Streak & early-bird bonuses ───────────────────────────────────────────
+
+    /// Sets the streak bonus: `(run length, points)` pairs in increasing run length. Each
+    /// run of scoring predictions within a phase earns the longest threshold it reaches.
+    /// Locked once the first bet is placed.
+    #[export]
+    pub fn set_streak_bonuses(&mut self, thresholds: Vec<(u32, u32)>) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.bets.is_empty() {
+            panic!("Streak bonuses locked after first bet");
+        }
+        if thresholds.len() > MAX_STREAK_THRESHOLDS {
+            panic!("Too many streak thresholds");
+        }
+        let mut previous: u32 = 1;
+        for (run_length, points) in thresholds.iter() {
+            if *run_length <= previous {
+                panic!("Run lengths must be increasing and above 1");
             }
-            _ => panic!("Result already proposed/finalized"),
+            if *points == 0 || *points > MAX_BONUS_POINTS {
+                panic!("Invalid bonus points");
+            }
+            previous = *run_length;
         }
 
-        self.emit_event(SmartCupEvent::ResultProposed(
-            match_id,
-            final_score,
-            penalty_winner,
-            oracle,
-            proposed_at.saturating_add(CHALLENGE_WINDOW_MS),
-        ))
-        .expect("event");
+        state.streak_thresholds = thresholds.clone();
+
+        self.emit_event(SmartCupEvent::StreakBonusesSet(thresholds))
+            .expect("event");
     }
 
-    // ── Admin: cancel wrong oracle proposal ──────────────────────────────────
+    /// Sets the early-bird bonus: `points` for every bet on a finalized match placed at
+    /// least `lead_ms` before kick-off (0 points disables it). Locked once the first bet
+    /// is placed.
     #[export]
-    pub fn cancel_proposed_result(&mut self, match_id: u64) {
+    pub fn set_early_bird_bonus(&mut self, lead_ms: u64, points: u32) {
         let state = SmartCupState::state_mut();
         state.only_admin();
 
-        let m = state.matches.get_mut(&match_id).expect("No such match");
+        if !state.bets.is_empty() {
+            panic!("Early-bird bonus locked after first bet");
+        }
+        if points > MAX_BONUS_POINTS {
+            panic!("Invalid bonus points");
+        }
+        if points > 0 && lead_ms == 0 {
+            panic!("Early-bird lead must be positive");
+        }
 
-        let oracle = match &m.result {
-            ResultStatus::Proposed { oracle, proposed_at, .. } => {
-                let expires_at = proposed_at.saturating_add(CHALLENGE_WINDOW_MS);
-                if exec::block_timestamp() >= expires_at {
-                    panic!("Challenge window expired — result is now final");
-                }
-                *oracle
-            },
-            ResultStatus::Unresolved => panic!("No proposal to cancel"),
-            ResultStatus::Finalized { .. } => panic!("Result already finalized — cannot cancel"),
-        };
+        state.early_bird_lead_ms = lead_ms;
+        state.early_bird_points = points;
 
-        m.result = ResultStatus::Unresolved;
+        self.emit_event(SmartCupEvent::EarlyBirdBonusSet(lead_ms, points))
+            .expect("event");
+    }
 
-        self.emit_event(SmartCupEvent::ResultProposalCancelled(match_id, oracle))
+    /// Credits streak and early-bird bonuses to the next `max_items` bettors of a phase
+    /// whose matches are all finalized or expired. Permissionless.
+    #[export]
+    pub fn settle_phase_bonuses(&mut self, phase: String, max_items: u32) {
+        if max_items == 0 {
+            panic!("max_items must be positive");
+        }
+
+        let state = SmartCupState::state_mut();
+        let (awarded, settled) = state.settle_phase_bonus_batch(&phase, max_items);
+
+        for (user, streak, early_bird) in awarded {
+            self.emit_event(SmartCupEvent::PhaseBonusAwarded(
+                user,
+                phase.clone(),
+                streak,
+                early_bird,
+            ))
             .expect("event");
+        }
+        if settled {
+            self.emit_event(SmartCupEvent::PhaseBonusesSettled(phase))
+                .expect("event");
+        }
     }
 
-    // ── Oracle: pull result directly from Oracle-Program ─────────────────────
+    // ── Parlays ────────────────────────────────────────────────────────────────
+
+    /// Places a parlay of `amount` over several matches, one `(match_id, score,
+    /// penalty_winner)` leg per match. The stake goes through the usual fee split and the
+    /// rest into the shared parlay pool. In native mode the attached value must equal
+    /// `amount`; in token mode it is pulled with `transfer_from`.
     #[export]
-    pub async fn propose_from_oracle(
+    pub async fn place_parlay(
         &mut self,
-        match_id: u64,
-        oracle_program_id: ActorId,
+        legs: Vec<(u64, Score, Option<PenaltyWinner>)>,
+        amount: u128,
     ) {
-        // 1. Verify oracle is authorized and match is still Unresolved
+        let owner = msg::source();
+        let legs: Vec<ParlayLeg> = legs
+            .into_iter()
+            .map(|(match_id, score, penalty_winner)| ParlayLeg {
+                match_id,
+                score,
+                penalty_winner,
+            })
+            .collect();
+
+        if let Err(reason) =
+            SmartCupState::state_ref().check_parlay(&legs, amount, exec::block_timestamp())
         {
-            let state = SmartCupState::state_mut();
-            if !state.authorized_oracles.get(&oracle_program_id).cloned().unwrap_or(false) {
-                panic!("Oracle not authorized");
-            }
-            let m = state.matches.get(&match_id).expect("No such match");
-            if !matches!(m.result, ResultStatus::Unresolved) {
-                panic!("Result already proposed or finalized");
-            }
+            panic!("{}", reason);
         }
 
-        // 2. Build sails-rs encoded call: (service_name, method_name, params)
-        //    Mirrors the TypeScript pattern: registry.createType('(String, String, u64)', ...)
-        let payload = {
-            use sails_rs::scale_codec::Encode;
-            ("Service", "QueryMatchResult", match_id).encode()
-        };
+        // Re-validate: a leg may have closed while awaiting the token
+        let recheck =
+            || SmartCupState::state_ref().check_parlay(&legs, amount, exec::block_timestamp());
+        if funds::collect_checked(owner, amount, recheck).await.is_err() {
+            self.emit_event(SmartCupEvent::PayoutFailed(owner, amount))
+                .expect("event");
+            return;
+        }
 
-        // 3. Cross-program query to Oracle-Program
-        //    Reply format: (String, String, Option<FinalResult>)
-        let reply_bytes = msg::send_bytes_for_reply(oracle_program_id, &payload, 0, 0)
-            .expect("Failed to send query to Oracle-Program")
+        let state = SmartCupState::state_mut();
+
+        let (parlay_id, pool_cut) = state.record_parlay(owner, legs.clone(), amount);
+
+        self.emit_event(SmartCupEvent::ParlayPlaced(parlay_id, owner, legs, pool_cut))
+            .expect("event");
+    }
+
+    /// Settles a parlay once every leg's match is finalized or expired. Legs on expired
+    /// matches are dropped; a parlay with no leg left is void and refundable. Permissionless.
+    #[export]
+    pub fn settle_parlay(&mut self, parlay_id: u64) {
+        let state = SmartCupState::state_mut();
+
+        let legs = {
+            let parlay = state.parlays.get(&parlay_id).expect("No such parlay");
+            if parlay.status != ParlayStatus::Open {
+                panic!("Parlay already settled");
+            }
+            parlay.legs.clone()
+        };
+        let (status, live_legs) = state.decide_parlay(&legs).expect("Parlay legs not settled");
+
+        let parlay = state.parlays.get_mut(&parlay_id).expect("No such parlay");
+        parlay.status = status;
+        parlay.live_legs = live_legs;
+        let stake = parlay.stake_in_pool;
+
+        match status {
+            ParlayStatus::Won => {
+                let weight = stake.saturating_mul(live_legs as u128);
+                state.parlay_winning_weight = state.parlay_winning_weight.saturating_add(weight);
+            }
+            ParlayStatus::Void => {
+                state.parlay_pool = state.parlay_pool.saturating_sub(stake);
+                state.parlay_refunds_owed = state.parlay_refunds_owed.saturating_add(stake);
+            }
+            _ => {}
+        }
+        state.parlays_open = state.parlays_open.saturating_sub(1);
+
+        self.emit_event(SmartCupEvent::ParlaySettled(parlay_id, status, live_legs))
+            .expect("event");
+    }
+
+    /// Fixes the parlay pool once every match is finalized (or expired) and every parlay
+    /// is settled. Parlays settle as their last leg finalizes, but a pari-mutuel share
+    /// needs every winner known, so payouts wait for this call. With no winning parlay the
+    /// pool goes to the final prize, which in turn waits for this call. Permissionless.
+    #[export]
+    pub fn finalize_parlay_pool(&mut self) {
+        let state = SmartCupState::state_mut();
+
+        if state.parlay_pool_finalized {
+            panic!("Parlay pool already finalized");
+        }
+        if state.final_prize_finalized {
+            panic!("Final prize already finalized");
+        }
+        for m in state.matches.values() {
+            if !matches!(m.result, ResultStatus::Finalized { .. } | ResultStatus::Expired) {
+                panic!("Not all matches finalized");
+            }
+        }
+        if state.parlays_open > 0 {
+            panic!("Not all parlays settled");
+        }
+
+        let pool = state.parlay_pool;
+        if state.parlay_winning_weight == 0 {
+            state.parlay_pool = 0;
+            state.final_prize_accumulated = state.final_prize_accumulated.saturating_add(pool);
+        }
+        state.parlay_payout_pool = pool;
+        state.parlay_pool_finalized = true;
+
+        self.emit_event(SmartCupEvent::ParlayPoolFinalized(pool, state.parlay_winning_weight))
+            .expect("event");
+    }
+
+    /// Pays a winning parlay its share of the pool (stake × live legs over the total
+    /// winning weight), or refunds the pool stake of a void parlay.
+    #[export]
+    pub async fn claim_parlay(&mut self, parlay_id: u64) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        let parlay = state.parlays.get_mut(&parlay_id).expect("No such parlay");
+        if parlay.owner != caller {
+            panic!("Not parlay owner");
+        }
+        if parlay.claimed {
+            panic!("Parlay already claimed");
+        }
+
+        let amount = match parlay.status {
+            ParlayStatus::Won => {
+                if !state.parlay_pool_finalized {
+                    panic!("Parlay pool not finalized");
+                }
+                parlay
+                    .stake_in_pool
+                    .saturating_mul(parlay.live_legs as u128)
+                    .saturating_mul(state.parlay_payout_pool)
+                    .checked_div(state.parlay_winning_weight)
+                    .unwrap_or(0)
+            }
+            ParlayStatus::Void => parlay.stake_in_pool,
+            ParlayStatus::Lost => panic!("Parlay lost"),
+            ParlayStatus::Open => panic!("Parlay not settled"),
+        };
+        let void = parlay.status == ParlayStatus::Void;
+
+        parlay.claimed = true;
+        if void {
+            state.parlay_refunds_owed = state.parlay_refunds_owed.saturating_sub(amount);
+        } else {
+            state.parlay_pool = state.parlay_pool.saturating_sub(amount);
+        }
+
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+        }
+
+        let state = SmartCupState::state_mut();
+        if let Some(parlay) = state.parlays.get_mut(&parlay_id) {
+            parlay.paid_out = amount;
+        }
+        let stats = state.user_stats.entry(caller).or_default();
+        if void {
+            stats.total_refunded = stats.total_refunded.saturating_add(amount);
+        } else {
+            stats.total_won = stats.total_won.saturating_add(amount);
+        }
+
+        self.emit_event(SmartCupEvent::ParlayClaimed(parlay_id, caller, amount))
+            .expect("event");
+    }
+
+    // ── Side markets ──────────────────────────────────────────────────────────
+
+    /// Opens a pari-mutuel market on a match that is still open for betting. Lines are in
+    /// hundredths of a goal and must be whole or half goals; a whole line can push, which
+    /// voids the market and refunds every stake.
+    #[export]
+    pub fn create_market(&mut self, match_id: u64, kind: MarketKind, params: MarketParams) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        let m = state.matches.get(&match_id).expect("Match not found");
+        if !matches!(m.result, ResultStatus::Unresolved) {
+            panic!("Match already resolved");
+        }
+        if exec::block_timestamp() >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS) {
+            panic!("Betting closed");
+        }
+        if params.line % 50 != 0 {
+            panic!("Line must be a whole or half goal");
+        }
+        if params.line.abs() > MAX_MARKET_LINE {
+            panic!("Line too large");
+        }
+        match kind {
+            MarketKind::OverUnder if params.line <= 0 => panic!("Over/under line must be positive"),
+            MarketKind::BothTeamsToScore if params.line != 0 => {
+                panic!("Both-teams-to-score takes no line")
+            }
+            _ => {}
+        }
+
+        let market_id = state.next_market_id;
+        state.next_market_id = state.next_market_id.saturating_add(1);
+        state.markets.insert(
+            market_id,
+            Market {
+                market_id,
+                match_id,
+                kind,
+                params,
+                pool: 0,
+                stakes: market_selections(kind).iter().map(|s| (*s, 0)).collect(),
+                status: MarketStatus::Open,
+                winning_selection: None,
+                winner_stake: 0,
+                claimed_winner_stake: 0,
+                total_claimed: 0,
+                dust_swept: false,
+            },
+        );
+
+        self.emit_event(SmartCupEvent::MarketCreated(market_id, match_id, kind, params))
+            .expect("event");
+    }
+
+    /// Bets `amount` on one selection of a market. In native mode the attached value must
+    /// equal `amount`; in token mode it is pulled with `transfer_from`.
+    #[export]
+    pub async fn place_market_bet(
+        &mut self,
+        market_id: u64,
+        selection: MarketSelection,
+        amount: u128,
+    ) {
+        let bettor = msg::source();
+
+        if let Err(reason) = SmartCupState::state_ref().check_market_bet(
+            bettor,
+            market_id,
+            selection,
+            amount,
+            exec::block_timestamp(),
+        ) {
+            panic!("{}", reason);
+        }
+
+        // Re-validate: betting may have closed or another bet landed while awaiting the token
+        let recheck = || {
+            SmartCupState::state_ref().check_market_bet(
+                bettor,
+                market_id,
+                selection,
+                amount,
+                exec::block_timestamp(),
+            )
+        };
+        if funds::collect_checked(bettor, amount, recheck).await.is_err() {
+            self.emit_event(SmartCupEvent::PayoutFailed(bettor, amount))
+                .expect("event");
+            return;
+        }
+
+        let state = SmartCupState::state_mut();
+
+        let pool_cut = state.record_market_bet(bettor, market_id, selection, amount);
+
+        self.emit_event(SmartCupEvent::MarketBetAccepted(market_id, bettor, selection, pool_cut))
+            .expect("event");
+    }
+
+    /// Settles a market from its match's finalized score, or voids it if the match expired
+    /// or the line pushed. With no stake on the winning selection the pool goes to the
+    /// final prize. Permissionless.
+    #[export]
+    pub fn settle_market(&mut self, market_id: u64) {
+        let state = SmartCupState::state_mut();
+
+        let market = state.markets.get_mut(&market_id).expect("No such market");
+        if market.status != MarketStatus::Open {
+            panic!("Market already settled");
+        }
+        let m = state.matches.get(&market.match_id).expect("No such match");
+
+        let winner = match m.result {
+            ResultStatus::Finalized { score, .. } => {
+                market_winner(market.kind, market.params, score)
+            }
+            ResultStatus::Expired => None,
+            _ => panic!("Match not finalized"),
+        };
+
+        let winner_stake = match winner {
+            Some(selection) => {
+                let winner_stake = market
+                    .stakes
+                    .iter()
+                    .find(|(s, _)| *s == selection)
+                    .map(|(_, stake)| *stake)
+                    .unwrap_or(0);
+                market.status = MarketStatus::Settled;
+                market.winning_selection = Some(selection);
+                market.winner_stake = winner_stake;
+
+                // No winner: the pool goes to the final prize right away
+                if winner_stake == 0 {
+                    state.final_prize_accumulated =
+                        state.final_prize_accumulated.saturating_add(market.pool);
+                    market.pool = 0;
+                    market.dust_swept = true;
+                }
+                winner_stake
+            }
+            None => {
+                market.status = MarketStatus::Void;
+                0
+            }
+        };
+
+        self.emit_event(SmartCupEvent::MarketSettled(market_id, winner, winner_stake))
+            .expect("event");
+    }
+
+    /// Pays the caller's share of a settled market, or refunds their stake on a void one.
+    #[export]
+    pub async fn claim_market_reward(&mut self, market_id: u64) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        let market = state.markets.get_mut(&market_id).expect("No such market");
+        let bet = state
+            .market_bets
+            .get_mut(&(caller, market_id))
+            .expect("No bet on this market");
+        if bet.claimed {
+            panic!("Already claimed");
+        }
+
+        let (amount, refund) = match market.status {
+            MarketStatus::Settled => {
+                if market.winning_selection != Some(bet.selection) {
+                    panic!("Not eligible for payout");
+                }
+                if market.dust_swept {
+                    panic!("Claim deadline passed");
+                }
+                let share = bet
+                    .stake_in_pool
+                    .saturating_mul(market.pool)
+                    .checked_div(market.winner_stake)
+                    .expect("Division by zero: winner_stake is zero");
+                (share, false)
+            }
+            MarketStatus::Void => (bet.stake_in_pool, true),
+            MarketStatus::Open => panic!("Market not settled"),
+        };
+        if amount == 0 {
+            panic!("Zero payout");
+        }
+
+        let stake = bet.stake_in_pool;
+        bet.claimed = true;
+        market.total_claimed = market.total_claimed.saturating_add(amount);
+        if !refund {
+            market.claimed_winner_stake = market.claimed_winner_stake.saturating_add(stake);
+        }
+
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+        }
+
+        let state = SmartCupState::state_mut();
+        if let Some(bet) = state.market_bets.get_mut(&(caller, market_id)) {
+            bet.paid_out = amount;
+        }
+        let stats = state.user_stats.entry(caller).or_default();
+        if refund {
+            stats.total_refunded = stats.total_refunded.saturating_add(amount);
+            self.emit_event(SmartCupEvent::MarketRefundClaimed(market_id, caller, amount))
+                .expect("event");
+        } else {
+            stats.total_won = stats.total_won.saturating_add(amount);
+            self.emit_event(SmartCupEvent::MarketRewardClaimed(market_id, caller, amount))
+                .expect("event");
+        }
+    }
+
+    /// Sweeps what is left of a settled market's pool to the final prize, once every
+    /// winner has claimed or the match's claim deadline has passed. Permissionless.
+    #[export]
+    pub fn sweep_market_dust_to_final_prize(&mut self, market_id: u64) {
+        let state = SmartCupState::state_mut();
+
+        let match_id = state.markets.get(&market_id).expect("No such market").match_id;
+        let m = state.matches.get(&match_id).expect("No such match");
+        let deadline_passed = m
+            .finalized_at
+            .map(|t| exec::block_timestamp() >= t.saturating_add(state.claim_deadline_ms(m)))
+            .unwrap_or(false);
+
+        let market = state.markets.get_mut(&market_id).expect("No such market");
+        if market.status != MarketStatus::Settled {
+            panic!("Market not settled");
+        }
+        if market.dust_swept {
+            panic!("Dust already swept");
+        }
+        if !deadline_passed && market.claimed_winner_stake < market.winner_stake {
+            panic!("Unclaimed eligible bets remain — wait for the claim deadline");
+        }
+
+        let dust = market.pool.saturating_sub(market.total_claimed);
+        state.final_prize_accumulated = state.final_prize_accumulated.saturating_add(dust);
+        market.pool = market.total_claimed;
+        market.dust_swept = true;
+
+        self.emit_event(SmartCupEvent::MarketDustSwept(market_id, dust))
+            .expect("event");
+    }
+
+    // ── Head-to-head challenges ───────────────────────────────────────────────
+
+    /// Opens a head-to-head challenge on a match, escrowing `stake`. `opponent` reserves it
+    /// for one wallet; `None` lets anyone accept. In native mode the attached value must
+    /// equal `stake`; in token mode it is pulled with `transfer_from`.
+    #[export]
+    pub async fn create_challenge(
+        &mut self,
+        match_id: u64,
+        opponent: Option<ActorId>,
+        my_prediction: Prediction,
+        stake: u128,
+    ) {
+        let creator = msg::source();
+
+        if let Err(reason) = SmartCupState::state_ref().check_challenge(
+            creator,
+            match_id,
+            opponent,
+            my_prediction,
+            stake,
+            exec::block_timestamp(),
+        ) {
+            panic!("{}", reason);
+        }
+
+        // Re-validate: betting may have closed while awaiting the token
+        let recheck = || {
+            SmartCupState::state_ref().check_challenge(
+                creator,
+                match_id,
+                opponent,
+                my_prediction,
+                stake,
+                exec::block_timestamp(),
+            )
+        };
+        if funds::collect_checked(creator, stake, recheck).await.is_err() {
+            self.emit_event(SmartCupEvent::PayoutFailed(creator, stake))
+                .expect("event");
+            return;
+        }
+
+        let state = SmartCupState::state_mut();
+
+        let challenge_id = state.next_challenge_id;
+        state.next_challenge_id = state.next_challenge_id.saturating_add(1);
+        state.challenges.insert(
+            challenge_id,
+            Challenge {
+                challenge_id,
+                match_id,
+                creator,
+                opponent,
+                creator_prediction: my_prediction,
+                acceptor: None,
+                acceptor_prediction: None,
+                stake,
+                status: ChallengeStatus::Open,
+                winner: None,
+            },
+        );
+        state.user_challenges.entry(creator).or_default().push(challenge_id);
+        state.challenge_escrow = state.challenge_escrow.saturating_add(stake);
+        let stats = state.user_stats.entry(creator).or_default();
+        stats.total_staked = stats.total_staked.saturating_add(stake);
+
+        self.emit_event(SmartCupEvent::ChallengeCreated(
+            challenge_id,
+            creator,
+            match_id,
+            opponent,
+            my_prediction,
+            stake,
+        ))
+        .expect("event");
+    }
+
+    /// Accepts an open challenge with a different prediction, escrowing the same stake.
+    #[export]
+    pub async fn accept_challenge(&mut self, challenge_id: u64, counter_prediction: Prediction) {
+        let acceptor = msg::source();
+
+        let stake = {
+            let state = SmartCupState::state_ref();
+            if let Err(reason) = state.check_accept_challenge(
+                acceptor,
+                challenge_id,
+                counter_prediction,
+                exec::block_timestamp(),
+            ) {
+                panic!("{}", reason);
+            }
+            state.challenges.get(&challenge_id).map(|c| c.stake).unwrap_or(0)
+        };
+
+        // Re-validate: the challenge may have been taken or betting closed meanwhile
+        let recheck = || {
+            SmartCupState::state_ref().check_accept_challenge(
+                acceptor,
+                challenge_id,
+                counter_prediction,
+                exec::block_timestamp(),
+            )
+        };
+        if funds::collect_checked(acceptor, stake, recheck).await.is_err() {
+            self.emit_event(SmartCupEvent::PayoutFailed(acceptor, stake))
+                .expect("event");
+            return;
+        }
+
+        let state = SmartCupState::state_mut();
+
+        let c = state.challenges.get_mut(&challenge_id).expect("Challenge not found");
+        c.acceptor = Some(acceptor);
+        c.acceptor_prediction = Some(counter_prediction);
+        c.status = ChallengeStatus::Accepted;
+        state.user_challenges.entry(acceptor).or_default().push(challenge_id);
+        state.challenge_escrow = state.challenge_escrow.saturating_add(stake);
+        let stats = state.user_stats.entry(acceptor).or_default();
+        stats.total_staked = stats.total_staked.saturating_add(stake);
+
+        self.emit_event(SmartCupEvent::ChallengeAccepted(
+            challenge_id,
+            acceptor,
+            counter_prediction,
+        ))
+        .expect("event");
+    }
+
+    /// Refunds an unaccepted challenge: the creator may cancel it at any time, anyone may
+    /// trigger the refund once betting on the match has closed.
+    #[export]
+    pub async fn refund_challenge(&mut self, challenge_id: u64) {
+        let state = SmartCupState::state_mut();
+
+        let c = state.challenges.get_mut(&challenge_id).expect("No such challenge");
+        if c.status != ChallengeStatus::Open {
+            panic!("Challenge not open");
+        }
+        if msg::source() != c.creator {
+            let close_time = state
+                .matches
+                .get(&c.match_id)
+                .map(|m| m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS))
+                .unwrap_or(0);
+            if exec::block_timestamp() < close_time {
+                panic!("Challenge still open for acceptance");
+            }
+        }
+
+        let (creator, stake) = (c.creator, c.stake);
+        c.status = ChallengeStatus::Refunded;
+        state.challenge_escrow = state.challenge_escrow.saturating_sub(stake);
+        let stats = state.user_stats.entry(creator).or_default();
+        stats.total_refunded = stats.total_refunded.saturating_add(stake);
+
+        self.emit_event(SmartCupEvent::ChallengeRefunded(challenge_id))
+            .expect("event");
+
+        if !funds::pay_out(creator, stake).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(creator, stake))
+                .expect("event");
+        }
+    }
+
+    /// Settles an accepted challenge once its match is finalized. The better prediction
+    /// (exact score over correct outcome over miss) takes both stakes minus the usual
+    /// protocol fee and final prize cut; equal predictions, or an expired match, refund
+    /// both sides. Permissionless.
+    #[export]
+    pub async fn settle_challenge(&mut self, challenge_id: u64) {
+        let state = SmartCupState::state_mut();
+
+        let c = state.challenges.get_mut(&challenge_id).expect("No such challenge");
+        if c.status != ChallengeStatus::Accepted {
+            panic!("Challenge not accepted");
+        }
+        let acceptor = c.acceptor.expect("Challenge not accepted");
+        let counter = c.acceptor_prediction.expect("Challenge not accepted");
+        let m = state.matches.get(&c.match_id).expect("No such match");
+
+        let winner = match m.result {
+            ResultStatus::Finalized { score, penalty_winner } => {
+                let phase_weight = state
+                    .phases
+                    .get(&m.phase)
+                    .map(|p| p.points_weight)
+                    .unwrap_or(1);
+                let rank = |p: Prediction| match prediction_outcome(
+                    p.score,
+                    p.penalty_winner,
+                    score,
+                    penalty_winner,
+                    phase_weight,
+                ) {
+                    BetOutcome::ExactScore => 2,
+                    BetOutcome::CorrectOutcome => 1,
+                    _ => 0,
+                };
+                match rank(c.creator_prediction).cmp(&rank(counter)) {
+                    core::cmp::Ordering::Greater => Some(c.creator),
+                    core::cmp::Ordering::Less => Some(acceptor),
+                    core::cmp::Ordering::Equal => None,
+                }
+            }
+            ResultStatus::Expired => None,
+            _ => panic!("Match not finalized"),
+        };
+
+        let creator = c.creator;
+        let stake = c.stake;
+        c.status = ChallengeStatus::Settled;
+        c.winner = winner;
+
+        let pot = stake.saturating_mul(2);
+        let (payouts, fee) = match winner {
+            Some(w) => {
+                // Each stake pays the usual fee split, referral share included
+                let (creator_prize_cut, creator_rest) = state.take_protocol_fee(creator, stake);
+                let (acceptor_prize_cut, acceptor_rest) = state.take_protocol_fee(acceptor, stake);
+                let final_prize_cut = creator_prize_cut.saturating_add(acceptor_prize_cut);
+                // Once the final prize is allocated the cut can no longer join it
+                if state.final_prize_finalized {
+                    state.protocol_fee_accumulated =
+                        state.protocol_fee_accumulated.saturating_add(final_prize_cut);
+                } else {
+                    state.final_prize_accumulated =
+                        state.final_prize_accumulated.saturating_add(final_prize_cut);
+                }
+                let payout = creator_rest.saturating_add(acceptor_rest);
+                (vec![(w, payout)], pot.saturating_sub(payout))
+            }
+            None => (vec![(creator, stake), (acceptor, stake)], 0),
+        };
+
+        state.challenge_escrow = state.challenge_escrow.saturating_sub(pot);
+        for (to, amount) in payouts.iter() {
+            let stats = state.user_stats.entry(*to).or_default();
+            if winner.is_some() {
+                stats.total_won = stats.total_won.saturating_add(*amount);
+            } else {
+                stats.total_refunded = stats.total_refunded.saturating_add(*amount);
+            }
+        }
+
+        let payout = pot.saturating_sub(fee);
+        self.emit_event(SmartCupEvent::ChallengeSettled(challenge_id, winner, payout, fee))
+            .expect("event");
+
+        for (to, amount) in funds::pay_or_park(payouts).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(to, amount))
+                .expect("event");
+        }
+    }
+
+    // ── Knockout bracket ──────────────────────────────────────────────────────
+
+    /// Adds a bracket slot. A first-round slot names its knockout match; a later slot
+    /// names its two feeder slots and may get its match later via `assign_bracket_match`.
+    /// Locked once the first bracket is submitted.
+    #[export]
+    pub fn register_bracket_slot(&mut self, match_id: Option<u64>, feeders: Option<(u32, u32)>) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.bracket_entrants.is_empty() {
+            panic!("Bracket locked after first submission");
+        }
+        let slot_id = state.bracket_slots.len() as u32;
+        match feeders {
+            Some((a, b)) => {
+                if a == b || a >= slot_id || b >= slot_id {
+                    panic!("Feeders must be two distinct earlier slots");
+                }
+            }
+            None => {
+                if match_id.is_none() {
+                    panic!("First-round slot needs a match");
+                }
+            }
+        }
+        if let Some(id) = match_id {
+            state.check_bracket_match(id);
+        }
+
+        state.bracket_slots.push(BracketSlot {
+            slot_id,
+            match_id,
+            feeders,
+            winner: None,
+            points: 0,
+            scoring_cursor: 0,
+            scored: false,
+        });
+
+        self.emit_event(SmartCupEvent::BracketSlotRegistered(slot_id, match_id, feeders))
+            .expect("event");
+    }
+
+    /// Ties a later-round slot to its knockout match once that match is registered.
+    #[export]
+    pub fn assign_bracket_match(&mut self, slot_id: u32, match_id: u64) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        state.check_bracket_match(match_id);
+        let slot = state
+            .bracket_slots
+            .get_mut(slot_id as usize)
+            .expect("No such slot");
+        if slot.match_id.is_some() {
+            panic!("Slot already has a match");
+        }
+        slot.match_id = Some(match_id);
+
+        self.emit_event(SmartCupEvent::BracketMatchAssigned(slot_id, match_id))
+            .expect("event");
+    }
+
+    /// Submits the caller's bracket — the advancing team for every slot, by slot id —
+    /// before the podium lock.
+    #[export]
+    pub fn submit_bracket(&mut self, picks: Vec<String>) {
+        let state = SmartCupState::state_mut();
+        let user = msg::source();
+
+        let lock = state.r32_lock_time.expect("R32 lock time not set");
+        if exec::block_timestamp() >= lock {
+            panic!("Bracket picks locked");
+        }
+        if state.bracket_entries.contains_key(&user) {
+            panic!("Bracket already submitted");
+        }
+        if let Err(reason) = state.check_bracket(&picks) {
+            panic!("{}", reason);
+        }
+
+        let index = state.bracket_entrants.len() as u32;
+        state.bracket_entrants.push(user);
+        state.bracket_entries.insert(user, BracketEntry { user, picks, index, points: 0 });
+
+        self.emit_event(SmartCupEvent::BracketSubmitted(user))
+            .expect("event");
+    }
+
+    /// Credits bracket points to the next `max_items` entrants of a decided slot from its
+    /// stored cursor. Permissionless.
+    #[export]
+    pub fn continue_bracket_scoring(&mut self, slot_id: u32, max_items: u32) {
+        if max_items == 0 {
+            panic!("max_items must be positive");
+        }
+
+        let state = SmartCupState::state_mut();
+        let (awarded, _complete) = state.score_bracket_batch(slot_id, max_items);
+
+        for (user, points) in awarded {
+            self.emit_event(SmartCupEvent::BracketPointsAwarded(user, slot_id, points))
+                .expect("event");
+        }
+    }
+
+    // ── Group standings ───────────────────────────────────────────────────────
+
+    /// Registers a group from its group-stage matches; its teams are the ones playing them.
+    /// Every match must still be open for betting.
+    #[export]
+    pub fn register_group(&mut self, name: String, match_ids: Vec<u64>) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if name.is_empty() || name.len() > MAX_PHASE_NAME_LEN {
+            panic!("Invalid group name length");
+        }
+        if state.groups.contains_key(&name) {
+            panic!("Group already registered");
+        }
+        let teams = match state.check_group(&match_ids, exec::block_timestamp()) {
+            Ok(teams) => teams,
+            Err(reason) => panic!("{}", reason),
+        };
+
+        state.groups.insert(
+            name.clone(),
+            Group {
+                name: name.clone(),
+                match_ids: match_ids.clone(),
+                teams,
+                standings: Vec::new(),
+                finalized: false,
+                void: false,
+                scoring_cursor: 0,
+                scored: false,
+            },
+        );
+
+        self.emit_event(SmartCupEvent::GroupRegistered(name, match_ids))
+            .expect("event");
+    }
+
+    /// Predicts the winner and runner-up of a group, before betting closes on its first match.
+    #[export]
+    pub fn submit_group_pick(&mut self, group: String, first: String, second: String) {
+        let state = SmartCupState::state_mut();
+        let user = msg::source();
+
+        let g = state.groups.get(&group).expect("No such group");
+        if state.group_started(g, exec::block_timestamp()) {
+            panic!("Group picks locked");
+        }
+        if state.group_picks.contains_key(&(user, group.clone())) {
+            panic!("Group pick already submitted");
+        }
+        if first == second {
+            panic!("First and second place must differ");
+        }
+        if !g.teams.contains(&first) || !g.teams.contains(&second) {
+            panic!("Team not in group");
+        }
+
+        let pickers = state.group_pickers.entry(group.clone()).or_default();
+        let index = pickers.len() as u32;
+        pickers.push(user);
+        state.group_picks.insert(
+            (user, group.clone()),
+            GroupPick { first: first.clone(), second: second.clone(), index },
+        );
+
+        self.emit_event(SmartCupEvent::GroupPickSubmitted(user, group, first, second))
+            .expect("event");
+    }
+
+    /// Fixes a group's table once all its matches are finalized; an expired match voids
+    /// the group's placement bonuses. Permissionless.
+    #[export]
+    pub fn finalize_group(&mut self, group: String) {
+        let state = SmartCupState::state_mut();
+
+        let event = match state.finalize_group(&group) {
+            Some(order) => SmartCupEvent::GroupFinalized(group, order),
+            None => SmartCupEvent::GroupVoided(group),
+        };
+        self.emit_event(event).expect("event");
+    }
+
+    /// Credits placement bonuses to the next `max_items` pickers of a finalized group from
+    /// its stored cursor. Permissionless.
+    #[export]
+    pub fn continue_group_scoring(&mut self, group: String, max_items: u32) {
+        if max_items == 0 {
+            panic!("max_items must be positive");
+        }
+
+        let state = SmartCupState::state_mut();
+        let (awarded, _complete) = state.score_group_batch(&group, max_items);
+
+        for (user, points) in awarded {
+            self.emit_event(SmartCupEvent::GroupBonusAwarded(user, group.clone(), points))
+                .expect("event");
+        }
+    }
+
+    // ── Survivor ──────────────────────────────────────────────────────────────
+
+    /// Sets the survivor entry fee. Locked once the first entrant has joined.
+    #[export]
+    pub fn set_survivor_entry_fee(&mut self, entry_fee: u128) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.survivor_entrants.is_empty() {
+            panic!("Entry fee locked after first entrant");
+        }
+        if entry_fee < MIN_BET_PLANCK {
+            panic!("Entry fee below minimum bet");
+        }
+
+        state.survivor_entry_fee = entry_fee;
+
+        self.emit_event(SmartCupEvent::SurvivorEntryFeeSet(entry_fee))
+            .expect("event");
+    }
+
+    /// Registers the next survivor round as a set of matches (a phase or a matchday) that
+    /// still take bets and are not part of an earlier round.
+    #[export]
+    pub fn register_survivor_round(&mut self, match_ids: Vec<u64>) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if let Err(reason) = state.check_survivor_round(&match_ids, exec::block_timestamp()) {
+            panic!("{}", reason);
+        }
+
+        let round_id = state.survivor_rounds.len() as u32;
+        state.survivor_rounds.push(SurvivorRound {
+            round_id,
+            match_ids: match_ids.clone(),
+            cursor: 0,
+            eliminated: 0,
+            settled: false,
+        });
+
+        self.emit_event(SmartCupEvent::SurvivorRoundRegistered(round_id, match_ids))
+            .expect("event");
+    }
+
+    /// Joins the survivor pool by paying the entry fee, before the first round's
+    /// betting closes.
+    #[export]
+    pub async fn join_survivor(&mut self) {
+        let entrant = msg::source();
+
+        let entry_fee = {
+            let state = SmartCupState::state_ref();
+            if state.survivor_entries.contains_key(&entrant) {
+                panic!("Already joined");
+            }
+            if state.survivor_started(exec::block_timestamp()) {
+                panic!("Survivor game already started");
+            }
+            state.survivor_entry_fee
+        };
+
+        // Re-validate: the first round may have started while awaiting the token
+        let recheck = || {
+            let state = SmartCupState::state_ref();
+            if state.survivor_entries.contains_key(&entrant)
+                || state.survivor_started(exec::block_timestamp())
+            {
+                return Err("Survivor game already started");
+            }
+            Ok(())
+        };
+        if funds::collect_checked(entrant, entry_fee, recheck).await.is_err() {
+            self.emit_event(SmartCupEvent::PayoutFailed(entrant, entry_fee))
+                .expect("event");
+            return;
+        }
+
+        let state = SmartCupState::state_mut();
+
+        let (final_prize_cut, pool_cut) = state.take_protocol_fee(entrant, entry_fee);
+        state.final_prize_accumulated =
+            state.final_prize_accumulated.saturating_add(final_prize_cut);
+        state.survivor_pool = state.survivor_pool.saturating_add(pool_cut);
+        state.survivor_entrants.push(entrant);
+        state.survivor_entries.insert(
+            entrant,
+            SurvivorEntry {
+                entrant,
+                alive: true,
+                eliminated_in: None,
+                picks: Vec::new(),
+                claimed: false,
+            },
+        );
+        state.survivor_alive = state.survivor_alive.saturating_add(1);
+        let stats = state.user_stats.entry(entrant).or_default();
+        stats.total_staked = stats.total_staked.saturating_add(entry_fee);
+
+        self.emit_event(SmartCupEvent::SurvivorJoined(entrant, pool_cut))
+            .expect("event");
+    }
+
+    /// Picks the team the caller backs to win in a round. A team can be used only once
+    /// and the pick closes with its match's betting window.
+    #[export]
+    pub fn survivor_pick(&mut self, round_id: u32, team: String) {
+        let state = SmartCupState::state_mut();
+        let entrant = msg::source();
+
+        let round = state
+            .survivor_rounds
+            .get(round_id as usize)
+            .expect("No such round");
+        if round.settled {
+            panic!("Round already settled");
+        }
+        let m = round
+            .match_ids
+            .iter()
+            .filter_map(|id| state.matches.get(id))
+            .find(|m| !team.is_empty() && (m.home == team || m.away == team))
+            .expect("Team does not play in this round");
+        if exec::block_timestamp() >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS) {
+            panic!("Betting closed");
+        }
+
+        let entry = state.survivor_entries.get_mut(&entrant).expect("Not a survivor entrant");
+        if !entry.alive {
+            panic!("Entrant eliminated");
+        }
+        if entry.picks.iter().any(|(r, _)| *r == round_id) {
+            panic!("Already picked this round");
+        }
+        if entry.picks.iter().any(|(_, used)| *used == team) {
+            panic!("Team already used");
+        }
+        entry.picks.push((round_id, team.clone()));
+
+        self.emit_event(SmartCupEvent::SurvivorPicked(entrant, round_id, team))
+            .expect("event");
+    }
+
+    /// Settles the next `max_items` entrants of a round once all its matches are finalized
+    /// or expired. Rounds settle in order. The game ends when at most one entrant is left,
+    /// or after the last registered round once every match is settled (see
+    /// `finish_survivor` if other matches are still pending then); if a round eliminates
+    /// everyone left, they share the pool. Permissionless.
+    #[export]
+    pub fn settle_survivor_round(&mut self, round_id: u32, max_items: u32) {
+        let state = SmartCupState::state_mut();
+
+        if state.survivor_finished {
+            panic!("Survivor game finished");
+        }
+        let round = state
+            .survivor_rounds
+            .get(round_id as usize)
+            .expect("No such round");
+        if round.settled {
+            panic!("Round already settled");
+        }
+        if round_id > 0 && !state.survivor_rounds[round_id as usize - 1].settled {
+            panic!("Previous round not settled");
+        }
+        for match_id in round.match_ids.iter() {
+            let m = state.matches.get(match_id).expect("No such match");
+            if !matches!(m.result, ResultStatus::Finalized { .. } | ResultStatus::Expired) {
+                panic!("Round matches not finalized");
+            }
+        }
+
+        let (eliminated, done) = state.settle_survivor_batch(round_id, max_items);
+        for entrant in eliminated {
+            self.emit_event(SmartCupEvent::SurvivorEliminated(entrant, round_id))
+                .expect("event");
+        }
+        if !done {
+            return;
+        }
+
+        self.emit_event(SmartCupEvent::SurvivorRoundSettled(round_id, state.survivor_alive))
+            .expect("event");
+
+        let last_round = round_id as usize + 1 == state.survivor_rounds.len();
+        let tournament_over = state
+            .matches
+            .values()
+            .all(|m| matches!(m.result, ResultStatus::Finalized { .. } | ResultStatus::Expired));
+        let winners = if state.survivor_alive == 0 {
+            state.survivor_wipeout_round = Some(round_id);
+            state.survivor_rounds[round_id as usize].eliminated
+        } else if state.survivor_alive == 1 || (last_round && tournament_over) {
+            state.survivor_alive
+        } else {
+            return;
+        };
+
+        state.finish_survivor(winners);
+
+        self.emit_event(SmartCupEvent::SurvivorFinished(winners, state.survivor_pool))
+            .expect("event");
+    }
+
+    /// Ends the survivor game once its last registered round is settled and every match
+    /// is finalized or expired. Covers a last round that left several entrants standing
+    /// while other matches were still pending. Permissionless.
+    #[export]
+    pub fn finish_survivor(&mut self) {
+        let state = SmartCupState::state_mut();
+
+        if state.survivor_finished {
+            panic!("Survivor game finished");
+        }
+        let last = state.survivor_rounds.last().expect("No survivor rounds");
+        if !last.settled {
+            panic!("Last round not settled");
+        }
+        let tournament_over = state
+            .matches
+            .values()
+            .all(|m| matches!(m.result, ResultStatus::Finalized { .. } | ResultStatus::Expired));
+        if !tournament_over {
+            panic!("Not all matches finalized");
+        }
+
+        let winners = state.survivor_alive;
+        state.finish_survivor(winners);
+
+        self.emit_event(SmartCupEvent::SurvivorFinished(winners, state.survivor_pool))
+            .expect("event");
+    }
+
+    /// Pays the caller's equal share of the survivor pool once the game has ended.
+    #[export]
+    pub async fn claim_survivor_prize(&mut self) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        if !state.survivor_finished {
+            panic!("Survivor game not finished");
+        }
+        let entry = state.survivor_entries.get_mut(&caller).expect("Not a survivor entrant");
+        let winner = entry.alive
+            || (state.survivor_wipeout_round.is_some()
+                && entry.eliminated_in == state.survivor_wipeout_round);
+        if !winner {
+            panic!("Not a survivor");
+        }
+        if entry.claimed {
+            panic!("Survivor prize already claimed");
+        }
+
+        let amount = state
+            .survivor_payout_pool
+            .checked_div(state.survivor_winners as u128)
+            .unwrap_or(0);
+        if amount == 0 {
+            panic!("Zero payout");
+        }
+
+        entry.claimed = true;
+        state.survivor_pool = state.survivor_pool.saturating_sub(amount);
+
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+        }
+
+        let stats = SmartCupState::state_mut().user_stats.entry(caller).or_default();
+        stats.total_won = stats.total_won.saturating_add(amount);
+
+        self.emit_event(SmartCupEvent::SurvivorPrizeClaimed(caller, amount))
+            .expect("event");
+    }
+
+    // ── Free-to-play ──────────────────────────────────────────────────────────
+
+    /// Records a zero-stake prediction. It earns points on the free-to-play leaderboard
+    /// only and never takes part in the match prize pool.
+    #[export]
+    pub fn place_prediction(
+        &mut self,
+        match_id: u64,
+        predicted_score: Score,
+        predicted_penalty_winner: Option<PenaltyWinner>,
+    ) {
+        let state = SmartCupState::state_mut();
+        let user = msg::source();
+
+        if msg::value() != 0 {
+            panic!("Free predictions take no stake");
+        }
+        if state.free_predictions.contains_key(&(user, match_id)) {
+            panic!("Already predicted");
+        }
+        let entries = state.matches.get(&match_id).map(|m| m.free_participants.len());
+        if entries.unwrap_or(0) >= MAX_FREE_PREDICTIONS_PER_MATCH {
+            panic!("Free prediction limit reached");
+        }
+        if let Err(reason) = state.check_prediction(
+            match_id,
+            predicted_score,
+            predicted_penalty_winner,
+            exec::block_timestamp(),
+        ) {
+            panic!("{}", reason);
+        }
+
+        state.free_predictions.insert(
+            (user, match_id),
+            FreePrediction {
+                user,
+                match_id,
+                score: predicted_score,
+                penalty_winner: predicted_penalty_winner,
+                points_credited: false,
+            },
+        );
+        state.user_free_predictions.entry(user).or_default().push(match_id);
+        let m = state.matches.get_mut(&match_id).expect("Match not found");
+        m.free_participants.push(user);
+
+        self.emit_event(SmartCupEvent::PredictionPlaced(
+            user,
+            match_id,
+            predicted_score,
+            predicted_penalty_winner,
+        ))
+        .expect("event");
+    }
+
+    /// Adds `amount` to the free-to-play prize pool. Anyone can sponsor it until the
+    /// pool is finalized.
+    #[export]
+    pub async fn fund_sponsor_pool(&mut self, amount: u128) {
+        let sponsor = msg::source();
+
+        if amount == 0 {
+            panic!("Amount must be positive");
+        }
+        if SmartCupState::state_ref().free_prize_finalized {
+            panic!("Free prize already finalized");
+        }
+
+        if !funds::collect(sponsor, amount).await {
+            panic!("Sponsor transfer failed");
+        }
+
+        let state = SmartCupState::state_mut();
+        state.sponsor_pool = state.sponsor_pool.saturating_add(amount);
+
+        self.emit_event(SmartCupEvent::SponsorPoolFunded(sponsor, amount))
+            .expect("event");
+    }
+
+    // ── Referrals ─────────────────────────────────────────────────────────────
+
+    /// Sets the share of the protocol fee (in BPS of the fee) that referrers earn.
+    #[export]
+    pub fn set_referral_fee_bps(&mut self, referral_fee_bps: u128) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if referral_fee_bps > BPS_DENOMINATOR {
+            panic!("Referral fee exceeds protocol fee");
+        }
+
+        state.referral_fee_bps = referral_fee_bps;
+
+        self.emit_event(SmartCupEvent::ReferralFeeBpsSet(referral_fee_bps))
+            .expect("event");
+    }
+
+    /// Registers `code` as the caller's referral code. One code per referrer.
+    #[export]
+    pub fn register_referrer(&mut self, code: String) {
+        let state = SmartCupState::state_mut();
+        let referrer = msg::source();
+
+        if code.len() < MIN_REFERRAL_CODE_LEN || code.len() > MAX_REFERRAL_CODE_LEN {
+            panic!("Invalid referral code length");
+        }
+        if state.referral_codes.contains_key(&code) {
+            panic!("Referral code taken");
+        }
+
+        let stats = state.referral_stats.entry(referrer).or_default();
+        if stats.code.is_some() {
+            panic!("Referrer already registered");
+        }
+        stats.code = Some(code.clone());
+        state.referral_codes.insert(code.clone(), referrer);
+
+        self.emit_event(SmartCupEvent::ReferrerRegistered(referrer, code))
+            .expect("event");
+    }
+
+    /// Links the caller to the referrer owning `code`. One-time, and only before the
+    /// caller's first bet.
+    #[export]
+    pub fn set_referrer(&mut self, code: String) {
+        let state = SmartCupState::state_mut();
+        let user = msg::source();
+
+        let referrer = *state.referral_codes.get(&code).expect("Unknown referral code");
+        if referrer == user {
+            panic!("Cannot refer yourself");
+        }
+        if state.referred_by.contains_key(&user) {
+            panic!("Referrer already set");
+        }
+        if state.user_bets.contains_key(&user) {
+            panic!("Referrer must be set before the first bet");
+        }
+
+        state.referred_by.insert(user, referrer);
+        let stats = state.referral_stats.entry(referrer).or_default();
+        stats.referred_users = stats.referred_users.saturating_add(1);
+
+        self.emit_event(SmartCupEvent::ReferrerSet(user, referrer))
+            .expect("event");
+    }
+
+    #[export]
+    pub async fn claim_referral_rewards(&mut self) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        let stats = state.referral_stats.get_mut(&caller).expect("Not a referrer");
+        let amount = stats.claimable;
+        if amount == 0 {
+            panic!("No referral rewards");
+        }
+
+        stats.claimable = 0;
+
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+        }
+
+        self.emit_event(SmartCupEvent::ReferralRewardsClaimed(caller, amount))
+            .expect("event");
+    }
+
+    // ── Oracle: result proposal ───────────────────────────────────────────────
+
+    #[export]
+    pub fn propose_result(
+        &mut self,
+        match_id: u64,
+        final_score: Score,
+        penalty_winner: Option<PenaltyWinner>,
+    ) {
+        let state = SmartCupState::state_mut();
+        state.only_oracle();
+
+        let oracle = msg::source();
+        let proposed_at = exec::block_timestamp();
+        let challenge_window_ms =
+            state.challenge_window_ms(state.matches.get(&match_id).expect("No such match"));
+        let m = state.matches.get_mut(&match_id).expect("No such match");
+        if m.home.is_empty() || m.away.is_empty() {
+            panic!("Teams not known yet");
+        }
+
+        match &m.result {
+            ResultStatus::Unresolved => {
+                m.result = ResultStatus::Proposed {
+                    score: final_score,
+                    penalty_winner,
+                    oracle,
+                    proposed_at,
+                };
+            }
+            _ => panic!("Result already proposed/finalized"),
+        }
+
+        self.emit_event(SmartCupEvent::ResultProposed(
+            match_id,
+            final_score,
+            penalty_winner,
+            oracle,
+            proposed_at.saturating_add(challenge_window_ms),
+        ))
+        .expect("event");
+    }
+
+    // ── Admin: cancel wrong oracle proposal ──────────────────────────────────
+    #[export]
+    pub async fn cancel_proposed_result(&mut self, match_id: u64) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        let challenge_window_ms =
+            state.challenge_window_ms(state.matches.get(&match_id).expect("No such match"));
+        let m = state.matches.get_mut(&match_id).expect("No such match");
+
+        let oracle = match &m.result {
+            ResultStatus::Proposed { oracle, proposed_at, .. } => {
+                let expires_at = proposed_at.saturating_add(challenge_window_ms);
+                if exec::block_timestamp() >= expires_at {
+                    panic!("Challenge window expired — result is now final");
+                }
+                *oracle
+            },
+            ResultStatus::Unresolved => panic!("No proposal to cancel"),
+            ResultStatus::Finalized { .. } => panic!("Result already finalized — cannot cancel"),
+            ResultStatus::Expired => panic!("Match expired — cannot cancel"),
+        };
+
+        m.result = ResultStatus::Unresolved;
+
+        // Cancelling the proposal vindicates everyone who disputed it
+        let refunds = state.uphold_open_disputes(match_id);
+
+        self.emit_event(SmartCupEvent::ResultProposalCancelled(match_id, oracle))
+            .expect("event");
+        for (index, _, _) in refunds.iter() {
+            self.emit_event(SmartCupEvent::DisputeResolved(match_id, *index, true))
+                .expect("event");
+        }
+
+        let bonds = refunds.into_iter().map(|(_, disputer, bond)| (disputer, bond)).collect();
+        for (disputer, bond) in funds::pay_or_park(bonds).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(disputer, bond))
+                .expect("event");
+        }
+    }
+
+    // ── Disputes ──────────────────────────────────────────────────────────────
+
+    /// Sets (or clears) the DAO program allowed to arbitrate disputes.
+    #[export]
+    pub fn set_dao_program(&mut self, dao_program: Option<ActorId>) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        state.dao_program = dao_program;
+
+        self.emit_event(SmartCupEvent::DaoProgramSet(dao_program))
+            .expect("event");
+    }
+
+    /// Disputes a proposed result during its challenge window. Only bettors on the match
+    /// can dispute, posting exactly `DISPUTE_BOND_PLANCK` (attached, or pulled from the
+    /// configured token). Freezes finalization until an admin or the DAO arbitrates every
+    /// open dispute.
+    #[export]
+    pub async fn dispute_result(
+        &mut self,
+        match_id: u64,
+        claimed_score: Score,
+        claimed_penalty_winner: Option<PenaltyWinner>,
+    ) {
+        let disputer = msg::source();
+
+        if let Err(reason) = SmartCupState::state_ref().check_dispute(
+            disputer,
+            match_id,
+            claimed_score,
+            claimed_penalty_winner,
+            exec::block_timestamp(),
+        ) {
+            panic!("{}", reason);
+        }
+
+        // Re-validate: the window may have closed while awaiting the token
+        let recheck = || {
+            SmartCupState::state_ref().check_dispute(
+                disputer,
+                match_id,
+                claimed_score,
+                claimed_penalty_winner,
+                exec::block_timestamp(),
+            )
+        };
+        if funds::collect_checked(disputer, DISPUTE_BOND_PLANCK, recheck).await.is_err() {
+            self.emit_event(SmartCupEvent::PayoutFailed(disputer, DISPUTE_BOND_PLANCK))
+                .expect("event");
+            return;
+        }
+
+        let state = SmartCupState::state_mut();
+        let now = exec::block_timestamp();
+
+        let list = state.disputes.entry(match_id).or_default();
+        let index = list.len() as u32;
+        list.push(Dispute {
+            disputer,
+            claimed_score,
+            claimed_penalty_winner,
+            bond: DISPUTE_BOND_PLANCK,
+            opened_at: now,
+            status: DisputeStatus::Open,
+        });
+        let m = state.matches.get_mut(&match_id).expect("No such match");
+        m.open_disputes = m.open_disputes.saturating_add(1);
+
+        self.emit_event(SmartCupEvent::ResultDisputed(
+            match_id,
+            index,
+            disputer,
+            claimed_score,
+            claimed_penalty_winner,
+        ))
+        .expect("event");
+    }
+
+    /// Arbitrates an open dispute. Upholding it cancels the proposal (back to Unresolved)
+    /// and refunds every open dispute bond on the match; rejecting it sends that bond to
+    /// the final prize pool, or to protocol fees once that is finalized. Admin or DAO only.
+    #[export]
+    pub async fn resolve_dispute(&mut self, match_id: u64, dispute_index: u32, upheld: bool) {
+        let state = SmartCupState::state_mut();
+        state.only_arbiter();
+
+        let dispute = state
+            .disputes
+            .get_mut(&match_id)
+            .and_then(|list| list.get_mut(dispute_index as usize))
+            .expect("No such dispute");
+        if dispute.status != DisputeStatus::Open {
+            panic!("Dispute already resolved");
+        }
+
+        if !upheld {
+            dispute.status = DisputeStatus::Rejected;
+            let bond = dispute.bond;
+            // Once the final prize is allocated the bond can no longer join it
+            if state.final_prize_finalized {
+                state.protocol_fee_accumulated = state.protocol_fee_accumulated.saturating_add(bond);
+            } else {
+                state.final_prize_accumulated = state.final_prize_accumulated.saturating_add(bond);
+            }
+
+            let m = state.matches.get_mut(&match_id).expect("No such match");
+            m.open_disputes = m.open_disputes.saturating_sub(1);
+
+            self.emit_event(SmartCupEvent::DisputeResolved(match_id, dispute_index, false))
+                .expect("event");
+            return;
+        }
+
+        let m = state.matches.get_mut(&match_id).expect("No such match");
+        let oracle = match &m.result {
+            ResultStatus::Proposed { oracle, .. } => *oracle,
+            _ => panic!("No proposal to overturn"),
+        };
+        m.result = ResultStatus::Unresolved;
+
+        let refunds = state.uphold_open_disputes(match_id);
+
+        self.emit_event(SmartCupEvent::ResultProposalCancelled(match_id, oracle))
+            .expect("event");
+        for (index, _, _) in refunds.iter() {
+            self.emit_event(SmartCupEvent::DisputeResolved(match_id, *index, true))
+                .expect("event");
+        }
+
+        let bonds = refunds.into_iter().map(|(_, disputer, bond)| (disputer, bond)).collect();
+        for (disputer, bond) in funds::pay_or_park(bonds).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(disputer, bond))
+                .expect("event");
+        }
+    }
+
+    // ── Oracle: pull result directly from Oracle-Program ─────────────────────
+    #[export]
+    pub async fn propose_from_oracle(
+        &mut self,
+        match_id: u64,
+        oracle_program_id: ActorId,
+    ) {
+        // 1. Verify oracle is authorized and match is still Unresolved
+        {
+            let state = SmartCupState::state_mut();
+            if !state.authorized_oracles.get(&oracle_program_id).cloned().unwrap_or(false) {
+                panic!("Oracle not authorized");
+            }
+            let m = state.matches.get(&match_id).expect("No such match");
+            if !matches!(m.result, ResultStatus::Unresolved) {
+                panic!("Result already proposed or finalized");
+            }
+            if m.home.is_empty() || m.away.is_empty() {
+                panic!("Teams not known yet");
+            }
+        }
+
+        // 2. Build sails-rs encoded call: (service_name, method_name, params)
+        //    Mirrors the TypeScript pattern: registry.createType('(String, String, u64)', ...)
+        let payload = {
+            use sails_rs::scale_codec::Encode;
+            ("Service", "QueryMatchResult", match_id).encode()
+        };
+
+        // 3. Cross-program query to Oracle-Program
+        //    Reply format: (String, String, Option<FinalResult>)
+        let reply_bytes = msg::send_bytes_for_reply(oracle_program_id, &payload, 0, 0)
+            .expect("Failed to send query to Oracle-Program")
             .await
             .expect("Oracle-Program query reply failed");
 
@@ -379,7 +2156,13 @@ impl Service {
         // 6. Set match to Proposed — challenge window begins now
         let proposed_at = exec::block_timestamp();
         let state = SmartCupState::state_mut();
+        let challenge_window_ms =
+            state.challenge_window_ms(state.matches.get(&match_id).expect("No such match"));
         let m = state.matches.get_mut(&match_id).expect("No such match");
+        // Another proposal or an expiry may have landed while awaiting the oracle.
+        if !matches!(m.result, ResultStatus::Unresolved) {
+            panic!("Result already proposed or finalized");
+        }
         m.result = ResultStatus::Proposed { score, penalty_winner, oracle: oracle_program_id, proposed_at };
 
         self.emit_event(SmartCupEvent::ResultProposed(
@@ -387,18 +2170,20 @@ impl Service {
             score,
             penalty_winner,
             oracle_program_id,
-            proposed_at.saturating_add(CHALLENGE_WINDOW_MS),
+            proposed_at.saturating_add(challenge_window_ms),
         ))
         .expect("event");
     }
 
-    // ── Result finalization + settlement (fused) ─────────────────────────────
+    // ── Result finalization + settlement ──────────────────────────────────────
 
     #[export]
     pub fn finalize_result(&mut self, match_id: u64) {
         let state = SmartCupState::state_mut();
         // Permissionless after challenge window — no only_admin() guard
 
+        let challenge_window_ms =
+            state.challenge_window_ms(state.matches.get(&match_id).expect("No such match"));
         let m = state.matches.get_mut(&match_id).expect("No such match");
 
         let (final_score, final_penalty_winner) = match &m.result {
@@ -408,7 +2193,7 @@ impl Service {
                 oracle: _,
                 proposed_at,
             } => {
-                let expires_at = proposed_at.saturating_add(CHALLENGE_WINDOW_MS);
+                let expires_at = proposed_at.saturating_add(challenge_window_ms);
                 if exec::block_timestamp() < expires_at {
                     panic!("Challenge window not expired yet");
                 }
@@ -417,6 +2202,10 @@ impl Service {
             _ => panic!("Not proposed or already finalized"),
         };
 
+        if m.open_disputes > 0 {
+            panic!("Result under dispute");
+        }
+
         let phase_weight = state
             .phases
             .get(&m.phase)
@@ -443,72 +2232,24 @@ impl Service {
             penalty_winner: final_penalty_winner,
         };
 
-        let final_outcome = if knockout {
-            advance_outcome(final_score, final_penalty_winner)
-        } else {
-            outcome(final_score)
-        };
-
-        // Combined loop: award points + accumulate winner stake in one pass
-        let mut total_winner_stake: u128 = 0;
-
-        for participant in m.participants.iter() {
-            if let Some(bet) = state.bets.get(&(*participant, match_id)) {
-                let mut added_points: u32 = 0;
-
-                let bet_outcome = if knockout {
-                    if bet.score.home == bet.score.away {
-                        if bet.penalty_winner.is_none() {
-                            0
-                        } else {
-                            advance_outcome(bet.score, bet.penalty_winner)
-                        }
-                    } else {
-                        outcome(bet.score)
-                    }
-                } else {
-                    outcome(bet.score)
-                };
-
-                let penalties_correct = if knockout && draw_final {
-                    bet.penalty_winner.is_some() && bet.penalty_winner == final_penalty_winner
-                } else {
-                    true
-                };
-
-                if bet.score == final_score && penalties_correct {
-                    added_points = 3u32.saturating_mul(phase_weight);
-                } else if bet_outcome == final_outcome {
-                    added_points = phase_weight;
-                }
-
-                if added_points > 0 {
-                    let pts = state.user_points.entry(*participant).or_insert(0);
-                    *pts = pts.saturating_add(added_points);
-
-                    self.emit_event(SmartCupEvent::PointsAwarded(
-                        *participant,
-                        match_id,
-                        added_points,
-                    ))
-                    .expect("event");
-                }
-
-                // Settlement: accumulate winner stake in the same pass
-                if eligible_for_payout(
-                    bet.score,
-                    bet.penalty_winner,
-                    final_score,
-                    final_penalty_winner,
-                    phase_weight,
-                ) {
-                    total_winner_stake =
-                        total_winner_stake.saturating_add(bet.stake_in_match_pool);
-                }
-            }
-        }
+        // Winner stake is two bucket lookups — no pass over participants. Exact hitters sit
+        // inside the winning side, so their extra weight is added on top of it.
+        let winning_side = payout_side(final_score, final_penalty_winner, phase_weight);
+        let jackpot_winner_stake = state
+            .exact_score_stake
+            .get(&(match_id, final_score, final_penalty_winner))
+            .cloned()
+            .unwrap_or(0);
+        let total_winner_stake = state
+            .side_stake
+            .get(&(match_id, winning_side))
+            .cloned()
+            .unwrap_or(0)
+            .saturating_add(
+                jackpot_winner_stake
+                    .saturating_mul(state.exact_score_weight.saturating_sub(1) as u128),
+            );
 
-        // Settle immediately — no separate prepare_match_settlement() needed
         if total_winner_stake == 0 {
             state.final_prize_accumulated = state
                 .final_prize_accumulated
@@ -518,8 +2259,21 @@ impl Service {
         }
         m.total_winner_stake = total_winner_stake;
         m.settlement_prepared = true;
+        m.points_settled = m.participants.is_empty();
+        m.free_points_settled = m.free_participants.is_empty();
         m.finalized_at = Some(exec::block_timestamp());
 
+        // Without exact hitters, the jackpot rolls over
+        m.jackpot_winner_stake = jackpot_winner_stake;
+        let jackpot = m.jackpot;
+        let rollover = if jackpot_winner_stake == 0 {
+            state.roll_over_jackpot(match_id)
+        } else {
+            None
+        };
+        let bracket_slot = state.decide_bracket_slot(match_id);
+        let placeholder = state.advance_winner(match_id);
+
         self.emit_event(SmartCupEvent::ResultFinalized(
             match_id,
             final_score,
@@ -529,17 +2283,112 @@ impl Service {
 
         self.emit_event(SmartCupEvent::SettlementPrepared(match_id, total_winner_stake))
             .expect("event");
+
+        if jackpot_winner_stake > 0 && jackpot > 0 {
+            self.emit_event(SmartCupEvent::JackpotWon(match_id, jackpot, jackpot_winner_stake))
+                .expect("event");
+        }
+        if let Some((next_match, amount)) = rollover {
+            self.emit_event(SmartCupEvent::JackpotRolledOver(match_id, next_match, amount))
+                .expect("event");
+        }
+        if let Some((slot_id, team, points)) = bracket_slot {
+            self.emit_event(SmartCupEvent::BracketSlotDecided(slot_id, team, points))
+                .expect("event");
+        }
+        if let Some(next_id) = placeholder {
+            let next = state.matches.get(&next_id).expect("No such match");
+            self.emit_event(SmartCupEvent::PlaceholderFilled(
+                next_id,
+                next.home.clone(),
+                next.away.clone(),
+            ))
+            .expect("event");
+        }
+    }
+
+    /// Credits points to the next `max_items` participants of a finalized match from the
+    /// stored cursor, so the leaderboard can be materialized without touching every
+    /// bettor in one message. Permissionless.
+    #[export]
+    pub fn continue_settlement(&mut self, match_id: u64, max_items: u32) {
+        if max_items == 0 {
+            panic!("max_items must be positive");
+        }
+
+        let state = SmartCupState::state_mut();
+        let (awarded, _complete) = state.settle_batch(match_id, max_items);
+
+        for (participant, added_points) in awarded {
+            self.emit_event(SmartCupEvent::PointsAwarded(participant, match_id, added_points))
+                .expect("event");
+        }
+
+        let m = state.matches.get(&match_id).expect("No such match");
+        self.emit_event(SmartCupEvent::SettlementProgress(
+            match_id,
+            m.settlement_cursor,
+            m.participants.len() as u32,
+        ))
+        .expect("event");
+    }
+
+    /// Credits free-to-play points to the next `max_items` free predictors of a finalized
+    /// match. Runs on its own cursor; only the free-to-play prize waits for it.
+    /// Permissionless.
+    #[export]
+    pub fn continue_free_settlement(&mut self, match_id: u64, max_items: u32) {
+        if max_items == 0 {
+            panic!("max_items must be positive");
+        }
+
+        let state = SmartCupState::state_mut();
+        let (awarded, _complete) = state.settle_free_batch(match_id, max_items);
+
+        for (predictor, added_points) in awarded {
+            self.emit_event(SmartCupEvent::FreePointsAwarded(predictor, match_id, added_points))
+                .expect("event");
+        }
+
+        let m = state.matches.get(&match_id).expect("No such match");
+        self.emit_event(SmartCupEvent::FreeSettlementProgress(
+            match_id,
+            m.free_settlement_cursor,
+            m.free_participants.len() as u32,
+        ))
+        .expect("event");
+    }
+
+    /// Credits the caller's pending points on every finalized match without waiting
+    /// for `continue_settlement` to reach them.
+    #[export]
+    pub fn sync_points(&mut self) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        let credited = state.sync_user_points(caller);
+        if credited.is_empty() {
+            panic!("No pending points");
+        }
+
+        for (match_id, added_points) in credited {
+            self.emit_event(SmartCupEvent::PointsAwarded(caller, match_id, added_points))
+                .expect("event");
+        }
     }
 
     // ── Settlement ────────────────────────────────────────────────────────────
 
     #[export]
-    pub fn claim_match_reward(&mut self, match_id: u64) {
+    pub async fn claim_match_reward(&mut self, match_id: u64) {
         let state = SmartCupState::state_mut();
         let caller = msg::source();
 
         let m = state.matches.get_mut(&match_id).expect("No such match");
 
+        if !m.settlement_prepared {
+            panic!("Settlement in progress");
+        }
         if m.match_prize_pool == 0 || m.total_winner_stake == 0 {
             panic!("No rewards for this match");
         }
@@ -576,25 +2425,61 @@ impl Service {
             panic!("Not eligible for payout");
         }
 
-        let share = bet
-            .stake_in_match_pool
+        let exact_hit = prediction_outcome(
+            bet.score,
+            bet.penalty_winner,
+            final_score,
+            final_penalty_winner,
+            phase_weight,
+        ) == BetOutcome::ExactScore;
+        let stake =
+            weighted_winner_stake(bet.stake_in_match_pool, exact_hit, state.exact_score_weight);
+
+        let share = stake
             .saturating_mul(m.match_prize_pool)
             .checked_div(m.total_winner_stake)
             .expect("Division by zero: total_winner_stake is zero");
 
-        if share == 0 {
-            bet.claimed = true;
+        let jackpot_amount = if exact_hit {
+            jackpot_share(bet.stake_in_match_pool, m.jackpot, m.jackpot_winner_stake)
+        } else {
+            0
+        };
+        // A tiny pool share can still carry a jackpot share, so check the total
+        let payout = share.saturating_add(jackpot_amount);
+        if payout == 0 {
             panic!("Zero payout");
         }
 
         bet.claimed = true;
         m.total_claimed = m.total_claimed.saturating_add(share);
+        m.claimed_winner_stake = m.claimed_winner_stake.saturating_add(stake);
+        m.jackpot_claimed = m.jackpot_claimed.saturating_add(jackpot_amount);
+
+        let credited = state.sync_user_points(caller);
+        for (credited_match, added_points) in credited {
+            self.emit_event(SmartCupEvent::PointsAwarded(caller, credited_match, added_points))
+                .expect("event");
+        }
 
-        msg::send_with_gas(caller, (), 0, share)
-            .unwrap_or_else(|_| panic!("Failed to send reward"));
+        if !funds::pay_out(caller, payout).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, payout))
+                .expect("event");
+        }
+
+        let state = SmartCupState::state_mut();
+        if let Some(bet) = state.bets.get_mut(&(caller, match_id)) {
+            bet.paid_out = payout;
+        }
+        let stats = state.user_stats.entry(caller).or_default();
+        stats.total_won = stats.total_won.saturating_add(payout);
 
         self.emit_event(SmartCupEvent::MatchRewardClaimed(match_id, caller, share))
             .expect("event");
+        if jackpot_amount > 0 {
+            self.emit_event(SmartCupEvent::JackpotClaimed(match_id, caller, jackpot_amount))
+                .expect("event");
+        }
     }
 
     // ── Dust sweep ────────────────────────────────────────────────────────────
@@ -606,8 +2491,10 @@ impl Service {
 
         {
             let m = state.matches.get(&match_id).expect("No such match");
+            // Expired matches sweep their unclaimed refunds on the same claim deadline
+            let expired = matches!(m.result, ResultStatus::Expired);
 
-            if !m.settlement_prepared {
+            if !expired && !m.settlement_prepared {
                 panic!("Settlement not prepared");
             }
             if m.dust_swept {
@@ -615,37 +2502,21 @@ impl Service {
             }
 
             if m.match_prize_pool > 0 {
-                let deadline_passed = m.finalized_at
-                    .map(|t| exec::block_timestamp() >= t.saturating_add(CLAIM_DEADLINE_MS))
+                let claim_deadline_ms = state.claim_deadline_ms(m);
+                let window_start = if expired { m.expired_at } else { m.finalized_at };
+                let deadline_passed = window_start
+                    .map(|t| exec::block_timestamp() >= t.saturating_add(claim_deadline_ms))
                     .unwrap_or(false);
 
-                // Before deadline: guard requires all winners to have claimed
-                if !deadline_passed {
-                    let (final_score, final_penalty_winner) = match m.result {
-                        ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
-                        _ => panic!("Match not finalized"),
-                    };
-                    let phase_weight = state
-                        .phases
-                        .get(&m.phase)
-                        .map(|p| p.points_weight)
-                        .unwrap_or(1);
-
-                    for participant in m.participants.iter() {
-                        if let Some(bet) = state.bets.get(&(*participant, match_id)) {
-                            if !bet.claimed
-                                && eligible_for_payout(
-                                    bet.score,
-                                    bet.penalty_winner,
-                                    final_score,
-                                    final_penalty_winner,
-                                    phase_weight,
-                                )
-                            {
-                                panic!("Unclaimed eligible bets remain — wait for 72h claim deadline");
-                            }
-                        }
-                    }
+                // Before deadline: guard requires all weighted winner stake to have been
+                // claimed, or every stake to have been refunded
+                let outstanding = if expired {
+                    m.total_claimed < m.match_prize_pool
+                } else {
+                    m.claimed_winner_stake < m.total_winner_stake
+                };
+                if !deadline_passed && outstanding {
+                    panic!("Unclaimed eligible bets remain — wait for the claim deadline");
                 }
                 // After deadline: sweep unconditionally, forfeiting unclaimed rewards
             }
@@ -660,14 +2531,110 @@ impl Service {
             return;
         }
 
-        let dust = m.match_prize_pool.saturating_sub(m.total_claimed);
+        // Unclaimed jackpot shares are forfeited along with the pool remainder
+        let dust = m
+            .match_prize_pool
+            .saturating_sub(m.total_claimed)
+            .saturating_add(m.jackpot.saturating_sub(m.jackpot_claimed));
         state.final_prize_accumulated =
             state.final_prize_accumulated.saturating_add(dust);
 
         m.match_prize_pool = 0;
+        m.jackpot = m.jackpot_claimed;
         m.dust_swept = true;
 
-        self.emit_event(SmartCupEvent::MatchDustSwept(match_id, dust))
+        self.emit_event(SmartCupEvent::MatchDustSwept(match_id, dust))
+            .expect("event");
+    }
+
+    // ── Unresolved matches: expiry & refunds ──────────────────────────────────
+
+    /// Expires a match whose result is still unresolved (or whose proposal is still
+    /// frozen by open disputes) after its resolution deadline, unlocking match-pool
+    /// refunds for its bettors. Open dispute bonds are refunded. Permissionless.
+    #[export]
+    pub async fn expire_match(&mut self, match_id: u64) {
+        let state = SmartCupState::state_mut();
+
+        let m = state.matches.get_mut(&match_id).expect("No such match");
+
+        let disputed = matches!(m.result, ResultStatus::Proposed { .. }) && m.open_disputes > 0;
+        if !matches!(m.result, ResultStatus::Unresolved) && !disputed {
+            panic!("Only unresolved or disputed matches can expire");
+        }
+        if exec::block_timestamp() < m.resolution_deadline {
+            panic!("Resolution deadline not reached");
+        }
+
+        m.result = ResultStatus::Expired;
+        m.expired_at = Some(exec::block_timestamp());
+        let refunds = if disputed { state.uphold_open_disputes(match_id) } else { Vec::new() };
+        let rollover = state.roll_over_jackpot(match_id);
+        let voided_slot = state.void_bracket_slot(match_id);
+
+        self.emit_event(SmartCupEvent::MatchExpired(match_id))
+            .expect("event");
+        if let Some(slot_id) = voided_slot {
+            self.emit_event(SmartCupEvent::BracketSlotVoided(slot_id))
+                .expect("event");
+        }
+        if let Some((next_match, amount)) = rollover {
+            self.emit_event(SmartCupEvent::JackpotRolledOver(match_id, next_match, amount))
+                .expect("event");
+        }
+        for (index, _, _) in refunds.iter() {
+            self.emit_event(SmartCupEvent::DisputeResolved(match_id, *index, true))
+                .expect("event");
+        }
+
+        let bonds = refunds.into_iter().map(|(_, disputer, bond)| (disputer, bond)).collect();
+        for (disputer, bond) in funds::pay_or_park(bonds).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(disputer, bond))
+                .expect("event");
+        }
+    }
+
+    /// Returns the caller's match-pool stake on an expired match.
+    /// Protocol fee and final prize cut taken at bet time are not refunded.
+    #[export]
+    pub async fn claim_refund(&mut self, match_id: u64) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        let m = state.matches.get_mut(&match_id).expect("No such match");
+        if !matches!(m.result, ResultStatus::Expired) {
+            panic!("Match not expired");
+        }
+        if m.dust_swept {
+            panic!("Refund claim deadline passed");
+        }
+
+        let bet = state
+            .bets
+            .get_mut(&(caller, match_id))
+            .expect("No bet for this match");
+        if bet.claimed {
+            panic!("Already refunded");
+        }
+
+        let amount = bet.stake_in_match_pool;
+
+        bet.claimed = true;
+        m.total_claimed = m.total_claimed.saturating_add(amount);
+
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+        }
+
+        let state = SmartCupState::state_mut();
+        if let Some(bet) = state.bets.get_mut(&(caller, match_id)) {
+            bet.paid_out = amount;
+        }
+        let stats = state.user_stats.entry(caller).or_default();
+        stats.total_refunded = stats.total_refunded.saturating_add(amount);
+
+        self.emit_event(SmartCupEvent::MatchRefundClaimed(match_id, caller, amount))
             .expect("event");
     }
 
@@ -711,6 +2678,12 @@ impl Service {
             },
         );
 
+        let credited = state.sync_user_points(user);
+        for (match_id, added_points) in credited {
+            self.emit_event(SmartCupEvent::PointsAwarded(user, match_id, added_points))
+                .expect("event");
+        }
+
         self.emit_event(SmartCupEvent::PodiumPickSubmitted(
             user,
             champion,
@@ -720,26 +2693,46 @@ impl Service {
         .expect("event");
     }
 
+    /// Marks the Final and the third-place match; the podium is derived from their results.
+    /// Can be changed until the podium is finalized, e.g. to replace an expired match.
     #[export]
-    pub fn finalize_podium(
-        &mut self,
-        champion: String,
-        runner_up: String,
-        third_place: String,
-    ) {
+    pub fn set_podium_matches(&mut self, final_match_id: u64, third_place_match_id: u64) {
         let state = SmartCupState::state_mut();
         state.only_admin();
 
         if state.podium_finalized {
             panic!("Already finalized");
         }
+        if final_match_id == third_place_match_id {
+            panic!("Final and third-place match must differ");
+        }
+        state.check_podium_match(state.final_match_id, final_match_id);
+        state.check_podium_match(state.third_place_match_id, third_place_match_id);
+
+        state.final_match_id = Some(final_match_id);
+        state.third_place_match_id = Some(third_place_match_id);
+
+        self.emit_event(SmartCupEvent::PodiumMatchesSet(final_match_id, third_place_match_id))
+            .expect("event");
+    }
+
+    /// Sets the official podium from the finalized Final and third-place match and awards
+    /// podium bonuses. Permissionless.
+    #[export]
+    pub fn finalize_podium(&mut self) {
+        let state = SmartCupState::state_mut();
+
+        if state.podium_finalized {
+            panic!("Already finalized");
+        }
+        let result = match state.podium_from_matches() {
+            Ok(result) => result,
+            Err(reason) => panic!("{}", reason),
+        };
 
         state.podium_finalized = true;
-        state.podium_result = Some(PodiumResult {
-            champion: champion.clone(),
-            runner_up: runner_up.clone(),
-            third_place: third_place.clone(),
-        });
+        state.podium_result = Some(result.clone());
+        let PodiumResult { champion, runner_up, third_place } = result;
 
         self.emit_event(SmartCupEvent::PodiumFinalized(
             champion.clone(),
@@ -777,152 +2770,505 @@ impl Service {
 
     // ── Final prize pool ──────────────────────────────────────────────────────
 
+    /// Replaces the prize curve used by `finalize_final_prize_pool` (and the free-to-play
+    /// prize). Locked once the first bet is placed.
+    #[export]
+    pub fn set_prize_curve(&mut self, curve: PrizeCurve) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.bets.is_empty() {
+            panic!("Prize curve locked after first bet");
+        }
+
+        match &curve {
+            PrizeCurve::Table(table) => {
+                if table.is_empty() || table.len() > MAX_PRIZE_POSITIONS {
+                    panic!("Invalid number of prize positions");
+                }
+                if table.contains(&0) {
+                    panic!("Prize shares must be positive");
+                }
+                let total = table.iter().try_fold(0u128, |acc, share| acc.checked_add(*share));
+                if total != Some(BPS_DENOMINATOR) {
+                    panic!("Prize shares must sum to BPS_DENOMINATOR");
+                }
+            }
+            PrizeCurve::ProportionalTopN(top_n) => {
+                if *top_n == 0 || *top_n as usize > MAX_PRIZE_POSITIONS {
+                    panic!("Invalid number of prize positions");
+                }
+            }
+            PrizeCurve::ProportionalTopBps(top_bps) => {
+                if *top_bps == 0 || *top_bps > BPS_DENOMINATOR {
+                    panic!("Invalid top share");
+                }
+            }
+        }
+
+        state.prize_curve = curve.clone();
+
+        self.emit_event(SmartCupEvent::PrizeCurveSet(curve))
+            .expect("event");
+    }
+
+    #[export]
+    pub async fn finalize_final_prize_pool(&mut self) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if state.final_prize_finalized {
+            panic!("Final prize already finalized");
+        }
+        if !state.podium_finalized {
+            panic!("Podium not finalized");
+        }
+        // A parlay pool without winners flows into the final prize
+        if !state.parlays.is_empty() && !state.parlay_pool_finalized {
+            panic!("Parlay pool not finalized");
+        }
+        for slot in state.bracket_slots.iter() {
+            if slot.winner.is_some() && !slot.scored {
+                panic!("Not all bracket slots scored");
+            }
+        }
+        if state.groups.values().any(|g| !g.scored) {
+            panic!("Not all groups scored");
+        }
+        // Phase prize dust flows into the final prize, so every funded phase settles first
+        for (phase, pool) in state.phase_prize_pools.iter() {
+            if *pool > 0 && !state.phase_prize_finalized.get(phase).cloned().unwrap_or(false) {
+                panic!("Not all phase prizes finalized");
+            }
+        }
+        if state.phase_bonuses_enabled() {
+            for phase in state.phase_bettors.keys() {
+                if !state.phase_bonus_settled.get(phase).cloned().unwrap_or(false) {
+                    panic!("Not all phase bonuses settled");
+                }
+            }
+        }
+        for market in state.markets.values() {
+            match market.status {
+                MarketStatus::Open => panic!("Not all markets settled"),
+                MarketStatus::Settled if !market.dust_swept => {
+                    panic!("Not all market dust swept")
+                }
+                _ => {}
+            }
+        }
+
+        for m in state.matches.values() {
+            match m.result {
+                ResultStatus::Finalized { .. } => {}
+                // Expired matches hold only refundable stakes, swept once their
+                // claim deadline passes
+                ResultStatus::Expired => {
+                    if !m.dust_swept {
+                        panic!("Not all match dust swept");
+                    }
+                    continue;
+                }
+                _ => panic!("Not all matches finalized"),
+            }
+            if !m.settlement_prepared {
+                panic!("Not all match settlements prepared");
+            }
+            if !m.points_settled {
+                panic!("Not all match points credited");
+            }
+            if !m.dust_swept {
+                panic!("Not all match dust swept");
+            }
+        }
+
+        let pool = state.final_prize_accumulated;
+        if pool == 0 {
+            panic!("No final prize pool");
+        }
+
+        let leaderboard = collect_leaderboard(state);
+        if leaderboard.is_empty() {
+            panic!("No participants");
+        }
+
+        let (allocations, total_allocated) = allocate_by_rank(&leaderboard, pool, &state.prize_curve);
+        for (wallet, amount) in allocations {
+            state.final_prize_allocations.insert(wallet, amount);
+            state.final_prize_claimed.insert(wallet, false);
+        }
+
+        if total_allocated == 0 {
+            panic!("Nothing allocated");
+        }
+
+        let dust = pool.saturating_sub(total_allocated);
+
+        state.final_prize_finalized = true;
+        state.final_prize_claimable_total = total_allocated;
+        state.final_prize_accumulated = 0;
+
+        state.final_prize_rounding_dust = 0;
+
+        self.emit_event(SmartCupEvent::FinalPrizePoolFinalized(total_allocated, dust))
+            .expect("event");
+
+        if dust > 0 {
+            let caller = msg::source();
+            if funds::pay_out(caller, dust).await {
+                self.emit_event(SmartCupEvent::FinalPrizeRoundingDustWithdrawn(dust, caller))
+                    .expect("event");
+            } else {
+                self.emit_event(SmartCupEvent::PayoutFailed(caller, dust))
+                    .expect("event");
+            }
+        }
+    }
+
+    #[export]
+    pub async fn claim_final_prize(&mut self) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        if !state.final_prize_finalized {
+            panic!("Final prize not finalized");
+        }
+
+        let already_claimed = state
+            .final_prize_claimed
+            .get(&caller)
+            .cloned()
+            .unwrap_or(false);
+
+        if already_claimed {
+            panic!("Final prize already claimed");
+        }
+
+        let amount = state
+            .final_prize_allocations
+            .get(&caller)
+            .cloned()
+            .unwrap_or(0);
+
+        if amount == 0 {
+            panic!("Not eligible for final prize");
+        }
+
+        state.final_prize_claimed.insert(caller, true);
+        state.final_prize_claimable_total =
+            state.final_prize_claimable_total.saturating_sub(amount);
+
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+        }
+
+        self.emit_event(SmartCupEvent::FinalPrizeClaimed(caller, amount))
+            .expect("event");
+    }
+
+    /// Retries the caller's parked payouts: any claim, refund, prize, bond or withdrawal
+    /// whose outbound transfer failed.
+    #[export]
+    pub async fn retry_failed_payout(&mut self) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        let amount = state.failed_payouts.remove(&caller).unwrap_or(0);
+        if amount == 0 {
+            panic!("No failed payouts");
+        }
+
+        if !funds::pay(caller, amount).await {
+            let owed = SmartCupState::state_mut().failed_payouts.entry(caller).or_insert(0);
+            *owed = owed.saturating_add(amount);
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+            return;
+        }
+
+        self.emit_event(SmartCupEvent::FailedPayoutRetried(caller, amount))
+            .expect("event");
+    }
+
+    /// Allocates the sponsor pool over the free-to-play leaderboard with the same prize
+    /// curve as the final prize. Rounding dust goes to the top-ranked predictor.
+    #[export]
+    pub fn finalize_free_prize_pool(&mut self) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if state.free_prize_finalized {
+            panic!("Free prize already finalized");
+        }
+
+        for m in state.matches.values() {
+            match m.result {
+                ResultStatus::Finalized { .. } => {}
+                ResultStatus::Expired => continue,
+                _ => panic!("Not all matches finalized"),
+            }
+            if !m.free_points_settled {
+                panic!("Not all free predictions credited");
+            }
+        }
+
+        let pool = state.sponsor_pool;
+        if pool == 0 {
+            panic!("No sponsor pool");
+        }
+
+        let leaderboard = collect_free_leaderboard(state);
+        if leaderboard.is_empty() {
+            panic!("No participants");
+        }
+
+        let (allocations, total_allocated) = allocate_by_rank(&leaderboard, pool, &state.prize_curve);
+        if total_allocated == 0 {
+            panic!("Nothing allocated");
+        }
+        let leader = allocations.first().map(|(wallet, _)| *wallet);
+        for (wallet, amount) in allocations {
+            state.free_prize_allocations.insert(wallet, amount);
+            state.free_prize_claimed.insert(wallet, false);
+        }
+
+        // Sponsor money stays with the players: rounding dust tops up the leader's share
+        let dust = pool.saturating_sub(total_allocated);
+        if let Some(share) = leader.and_then(|w| state.free_prize_allocations.get_mut(&w)) {
+            *share = share.saturating_add(dust);
+        }
+        state.free_prize_finalized = true;
+
+        self.emit_event(SmartCupEvent::FreePrizePoolFinalized(total_allocated, dust))
+            .expect("event");
+    }
+
+    #[export]
+    pub async fn claim_free_prize(&mut self) {
+        let state = SmartCupState::state_mut();
+        let caller = msg::source();
+
+        if !state.free_prize_finalized {
+            panic!("Free prize not finalized");
+        }
+        if state.free_prize_claimed.get(&caller).cloned().unwrap_or(false) {
+            panic!("Free prize already claimed");
+        }
+        let amount = state.free_prize_allocations.get(&caller).cloned().unwrap_or(0);
+        if amount == 0 {
+            panic!("Not eligible for free prize");
+        }
+
+        state.free_prize_claimed.insert(caller, true);
+        state.sponsor_pool = state.sponsor_pool.saturating_sub(amount);
+
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
+        }
+
+        self.emit_event(SmartCupEvent::FreePrizeClaimed(caller, amount))
+            .expect("event");
+    }
+
+    // ── Exact-score jackpot ─────────────────────────────────────────────────────
+
+    /// Sets the slice of every bet (in BPS of the bet) taken from the match pool cut into
+    /// the match's exact-score jackpot. Locked once the first bet is placed.
+    #[export]
+    pub fn set_jackpot_bps(&mut self, jackpot_bps: u128) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.bets.is_empty() {
+            panic!("Jackpot share locked after first bet");
+        }
+        if jackpot_bps > MAX_JACKPOT_BPS {
+            panic!("Jackpot share too high");
+        }
+
+        state.jackpot_bps = jackpot_bps;
+
+        self.emit_event(SmartCupEvent::JackpotBpsSet(jackpot_bps))
+            .expect("event");
+    }
+
+    /// Sets how many times an exact-score winner's stake counts in the match reward split,
+    /// so precision pays more than a right outcome. Locked once the first bet is placed.
+    #[export]
+    pub fn set_exact_score_weight(&mut self, weight: u32) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.bets.is_empty() {
+            panic!("Exact-score weight locked after first bet");
+        }
+        if !(1..=MAX_EXACT_SCORE_WEIGHT).contains(&weight) {
+            panic!("Invalid exact-score weight");
+        }
+
+        state.exact_score_weight = weight;
+
+        self.emit_event(SmartCupEvent::ExactScoreWeightSet(weight))
+            .expect("event");
+    }
+
+    // ── Phase prizes ────────────────────────────────────────────────────────────
+
+    /// Sets the slice of every bet (in BPS of the bet) diverted from the final prize cut
+    /// to the prize of the bet's phase. Locked once the first bet is placed.
+    #[export]
+    pub fn set_phase_prize_bps(&mut self, phase_prize_bps: u128) {
+        let state = SmartCupState::state_mut();
+        state.only_admin();
+
+        if !state.bets.is_empty() {
+            panic!("Phase prize share locked after first bet");
+        }
+        if phase_prize_bps > FINAL_PRIZE_BPS {
+            panic!("Phase prize exceeds final prize cut");
+        }
+
+        state.phase_prize_bps = phase_prize_bps;
+
+        self.emit_event(SmartCupEvent::PhasePrizeBpsSet(phase_prize_bps))
+            .expect("event");
+    }
+
+    /// Allocates a phase's prize over the phase leaderboard along the prize curve, once
+    /// every match of the phase is finalized (or expired) and its points are credited.
+    /// Rounding dust goes to the final prize pool; the final prize waits for every funded
+    /// phase prize. Admin only.
     #[export]
-    pub fn finalize_final_prize_pool(&mut self) {
+    pub fn finalize_phase_prize(&mut self, phase: String) {
         let state = SmartCupState::state_mut();
         state.only_admin();
 
-        if state.final_prize_finalized {
-            panic!("Final prize already finalized");
+        if !state.phases.contains_key(&phase) {
+            panic!("Phase not found");
         }
-        if !state.podium_finalized {
-            panic!("Podium not finalized");
+        if state.phase_prize_finalized.get(&phase).cloned().unwrap_or(false) {
+            panic!("Phase prize already finalized");
         }
 
-        for m in state.matches.values() {
+        for m in state.matches.values().filter(|m| m.phase == phase) {
             match m.result {
                 ResultStatus::Finalized { .. } => {}
-                _ => panic!("Not all matches finalized"),
+                ResultStatus::Expired => continue,
+                _ => panic!("Not all phase matches finalized"),
             }
-            if !m.settlement_prepared {
-                panic!("Not all match settlements prepared");
-            }
-            if !m.dust_swept {
-                panic!("Not all match dust swept");
+            if !m.points_settled {
+                panic!("Not all phase match points credited");
             }
         }
 
-        let pool = state.final_prize_accumulated;
-        if pool == 0 {
-            panic!("No final prize pool");
-        }
-
-        let leaderboard = collect_leaderboard(state);
-        if leaderboard.is_empty() {
-            panic!("No participants");
+        if state.phase_bonuses_enabled()
+            && !state.phase_bonus_settled.get(&phase).cloned().unwrap_or(false)
+        {
+            panic!("Phase bonuses not settled");
         }
 
-        let mut i: usize = 0;
-        let mut current_position: usize = 1;
-        let mut total_allocated: u128 = 0;
-
-        while i < leaderboard.len() && current_position <= 5 {
-            let tied_points = leaderboard[i].1;
-            let mut j = i + 1;
-
-            while j < leaderboard.len() && leaderboard[j].1 == tied_points {
-                j += 1;
-            }
-
-            let group_size = j - i; // always >= 1 by loop invariant
-            let start_pos = current_position;
-            let end_pos = current_position + group_size - 1;
-            let affected_end = end_pos.min(5);
-
-            if start_pos <= 5 {
-                let group_bps = top5_share_sum_bps(start_pos, affected_end);
-                if group_bps > 0 {
-                    let group_amount = pool.saturating_mul(group_bps) / BPS_DENOMINATOR;
-
-                    let per_wallet = group_amount
-                        .checked_div(group_size as u128)
-                        .expect("Division by zero: group_size is zero");
-
-                    if per_wallet > 0 {
-                        for k in i..j {
-                            let wallet = leaderboard[k].0;
-                            state.final_prize_allocations.insert(wallet, per_wallet);
-                            state.final_prize_claimed.insert(wallet, false);
-                        }
-                        total_allocated = total_allocated
-                            .saturating_add(per_wallet.saturating_mul(group_size as u128));
-                    }
-                }
-            }
-
-            current_position = current_position.saturating_add(group_size);
-            i = j;
+        let pool = state.phase_prize_pools.get(&phase).cloned().unwrap_or(0);
+        if pool == 0 {
+            panic!("No phase prize pool");
         }
 
-        if total_allocated == 0 {
-            panic!("Nothing allocated");
+        let leaderboard = collect_phase_leaderboard(state, &phase);
+        let (allocations, total_allocated) =
+            allocate_by_rank(&leaderboard, pool, &state.prize_curve);
+        for (wallet, amount) in allocations {
+            state.phase_prize_allocations.insert((phase.clone(), wallet), amount);
+            state.phase_prize_claimed.insert((phase.clone(), wallet), false);
         }
 
         let dust = pool.saturating_sub(total_allocated);
-
-        state.final_prize_finalized = true;
-        state.final_prize_claimable_total = total_allocated;
-        state.final_prize_accumulated = 0;
-
-        if dust > 0 {
-            let caller = msg::source();
-            state.final_prize_rounding_dust = 0;
-            msg::send(caller, (), dust).expect("Dust auto-sweep failed");
-            self.emit_event(SmartCupEvent::FinalPrizeRoundingDustWithdrawn(dust, caller))
-                .expect("event");
+        // Once the final prize is allocated the dust can no longer join it
+        if state.final_prize_finalized {
+            state.protocol_fee_accumulated = state.protocol_fee_accumulated.saturating_add(dust);
         } else {
-            state.final_prize_rounding_dust = 0;
+            state.final_prize_accumulated = state.final_prize_accumulated.saturating_add(dust);
         }
+        state.phase_prize_pools.insert(phase.clone(), total_allocated);
+        state.phase_prize_finalized.insert(phase.clone(), true);
+        state.phase_prize_finalized_at.insert(phase.clone(), exec::block_timestamp());
 
-        self.emit_event(SmartCupEvent::FinalPrizePoolFinalized(total_allocated, dust))
+        self.emit_event(SmartCupEvent::PhasePrizeFinalized(phase, total_allocated, dust))
             .expect("event");
     }
 
     #[export]
-    pub fn claim_final_prize(&mut self) {
+    pub async fn claim_phase_prize(&mut self, phase: String) {
         let state = SmartCupState::state_mut();
         let caller = msg::source();
+        let key = (phase.clone(), caller);
 
-        if !state.final_prize_finalized {
-            panic!("Final prize not finalized");
+        if !state.phase_prize_finalized.get(&phase).cloned().unwrap_or(false) {
+            panic!("Phase prize not finalized");
+        }
+        let finalized_at = state.phase_prize_finalized_at.get(&phase).cloned().unwrap_or(0);
+        let deadline = finalized_at.saturating_add(state.phase_claim_deadline_ms(&phase));
+        if exec::block_timestamp() >= deadline {
+            panic!("Phase prize claim deadline passed");
+        }
+        if state.phase_prize_claimed.get(&key).cloned().unwrap_or(false) {
+            panic!("Phase prize already claimed");
+        }
+        let amount = state.phase_prize_allocations.get(&key).cloned().unwrap_or(0);
+        if amount == 0 {
+            panic!("Not eligible for phase prize");
         }
 
-        let already_claimed = state
-            .final_prize_claimed
-            .get(&caller)
-            .cloned()
-            .unwrap_or(false);
+        state.phase_prize_claimed.insert(key.clone(), true);
+        let pool = state.phase_prize_pools.entry(phase.clone()).or_insert(0);
+        *pool = pool.saturating_sub(amount);
 
-        if already_claimed {
-            panic!("Final prize already claimed");
+        if !funds::pay_out(caller, amount).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(caller, amount))
+                .expect("event");
         }
 
-        let amount = state
-            .final_prize_allocations
-            .get(&caller)
-            .cloned()
-            .unwrap_or(0);
+        self.emit_event(SmartCupEvent::PhasePrizeClaimed(phase, caller, amount))
+            .expect("event");
+    }
 
-        if amount == 0 {
-            panic!("Not eligible for final prize");
-        }
+    /// Sweeps a phase prize's unclaimed allocations once its claim deadline has passed:
+    /// into the final prize while it is still open, otherwise to the protocol fees.
+    /// Permissionless.
+    #[export]
+    pub fn sweep_phase_prize(&mut self, phase: String) {
+        let state = SmartCupState::state_mut();
+        // Permissionless — no only_admin() guard
 
-        // CEI: update state BEFORE external send
-        state.final_prize_claimed.insert(caller, true);
-        state.final_prize_claimable_total =
-            state.final_prize_claimable_total.saturating_sub(amount);
+        if !state.phase_prize_finalized.get(&phase).cloned().unwrap_or(false) {
+            panic!("Phase prize not finalized");
+        }
+        let finalized_at = state.phase_prize_finalized_at.get(&phase).cloned().unwrap_or(0);
+        let deadline = finalized_at.saturating_add(state.phase_claim_deadline_ms(&phase));
+        if exec::block_timestamp() < deadline {
+            panic!("Phase prize claim deadline not reached");
+        }
+        let unclaimed = state.phase_prize_pools.get(&phase).cloned().unwrap_or(0);
+        if unclaimed == 0 {
+            panic!("Nothing to sweep");
+        }
 
-        msg::send_with_gas(caller, (), 0, amount)
-            .unwrap_or_else(|_| panic!("Failed to send final prize"));
+        state.phase_prize_pools.insert(phase.clone(), 0);
+        if state.final_prize_finalized {
+            state.protocol_fee_accumulated =
+                state.protocol_fee_accumulated.saturating_add(unclaimed);
+        } else {
+            state.final_prize_accumulated = state.final_prize_accumulated.saturating_add(unclaimed);
+        }
 
-        self.emit_event(SmartCupEvent::FinalPrizeClaimed(caller, amount))
+        self.emit_event(SmartCupEvent::PhasePrizeSwept(phase, unclaimed))
             .expect("event");
     }
 
     // ── Admin: withdrawals ────────────────────────────────────────────────────
 
     #[export]
-    pub fn withdraw_protocol_fees(&mut self) {
+    pub async fn withdraw_protocol_fees(&mut self) {
         let state = SmartCupState::state_mut();
         state.only_admin();
 
@@ -934,14 +3280,17 @@ impl Service {
         }
 
         state.protocol_fee_accumulated = 0;
-        msg::send(to, (), amt).expect("Fee transfer failed");
+        if !funds::pay_out(to, amt).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(to, amt))
+                .expect("event");
+        }
 
         self.emit_event(SmartCupEvent::ProtocolFeesWithdrawn(amt, to))
             .expect("event");
     }
 
     #[export]
-    pub fn withdraw_final_prize_rounding_dust(&mut self) {
+    pub async fn withdraw_final_prize_rounding_dust(&mut self) {
         let state = SmartCupState::state_mut();
         state.only_admin();
 
@@ -956,7 +3305,10 @@ impl Service {
 
         let to = msg::source();
         state.final_prize_rounding_dust = 0;
-        msg::send(to, (), amt).expect("Final prize rounding dust transfer failed");
+        if !funds::pay_out(to, amt).await {
+            self.emit_event(SmartCupEvent::PayoutFailed(to, amt))
+                .expect("event");
+        }
 
         self.emit_event(SmartCupEvent::FinalPrizeRoundingDustWithdrawn(amt, to))
             .expect("event");
@@ -1011,13 +3363,28 @@ impl Service {
         SmartCupState::state_ref().matches.get(&match_id).cloned()
     }
 
+    /// Credited points plus points already earned on finalized matches but not yet synced.
     #[export]
     pub fn query_user_points(&self, user: ActorId) -> u32 {
-        SmartCupState::state_ref()
-            .user_points
-            .get(&user)
-            .cloned()
-            .unwrap_or(0)
+        SmartCupState::state_ref().total_points(user)
+    }
+
+    /// Free-to-play points, including predictions not yet reached by the settlement crank.
+    #[export]
+    pub fn query_free_points(&self, user: ActorId) -> u32 {
+        SmartCupState::state_ref().total_free_points(user)
+    }
+
+    /// Phase leaderboard from materialized points, best first.
+    #[export]
+    pub fn query_phase_leaderboard(&self, phase: String) -> Vec<(ActorId, u32)> {
+        collect_phase_leaderboard(SmartCupState::state_ref(), &phase)
+    }
+
+    /// Free-to-play leaderboard from materialized points, best first.
+    #[export]
+    pub fn query_free_leaderboard(&self) -> Vec<(ActorId, u32)> {
+        collect_free_leaderboard(SmartCupState::state_ref())
     }
 
     #[export]
@@ -1060,10 +3427,6 @@ impl Service {
                 None => continue,
             };
 
-            if !m.settlement_prepared || m.match_prize_pool == 0 || m.total_winner_stake == 0 {
-                continue;
-            }
-
             let bet = match state.bets.get(&(wallet, r.match_id)) {
                 Some(b) => b,
                 None => continue,
@@ -1073,6 +3436,18 @@ impl Service {
                 continue;
             }
 
+            if matches!(m.result, ResultStatus::Expired) {
+                if bet.stake_in_match_pool > 0 && !m.dust_swept {
+                    total_claimable = total_claimable.saturating_add(bet.stake_in_match_pool);
+                    has_unclaimed_eligible = true;
+                }
+                continue;
+            }
+
+            if !m.settlement_prepared || m.match_prize_pool == 0 || m.total_winner_stake == 0 {
+                continue;
+            }
+
             let (final_score, final_penalty_winner) = match m.result {
                 ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
                 _ => continue,
@@ -1096,11 +3471,25 @@ impl Service {
                 continue;
             }
 
-            let claimable = bet
-                .stake_in_match_pool
-                .saturating_mul(m.match_prize_pool)
-                .checked_div(m.total_winner_stake)
-                .unwrap_or(0);
+            let exact_hit = prediction_outcome(
+                bet.score,
+                bet.penalty_winner,
+                final_score,
+                final_penalty_winner,
+                phase_weight,
+            ) == BetOutcome::ExactScore;
+            let mut claimable =
+                weighted_winner_stake(bet.stake_in_match_pool, exact_hit, state.exact_score_weight)
+                    .saturating_mul(m.match_prize_pool)
+                    .checked_div(m.total_winner_stake)
+                    .unwrap_or(0);
+            if exact_hit {
+                claimable = claimable.saturating_add(jackpot_share(
+                    bet.stake_in_match_pool,
+                    m.jackpot,
+                    m.jackpot_winner_stake,
+                ));
+            }
 
             if claimable > 0 {
                 total_claimable = total_claimable.saturating_add(claimable);
@@ -1115,6 +3504,24 @@ impl Service {
         }
     }
 
+    #[export]
+    pub fn query_referral_stats(&self, referrer: ActorId) -> ReferralStats {
+        SmartCupState::state_ref()
+            .referral_stats
+            .get(&referrer)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[export]
+    pub fn query_disputes(&self, match_id: u64) -> Vec<Dispute> {
+        SmartCupState::state_ref()
+            .disputes
+            .get(&match_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     #[export]
     pub fn query_bets_by_user(&self, user: ActorId) -> Vec<UserBetView> {
         let state = SmartCupState::state_ref();
@@ -1143,11 +3550,332 @@ impl Service {
         out
     }
 
+    #[export]
+    pub fn query_parlay(&self, parlay_id: u64) -> Option<Parlay> {
+        SmartCupState::state_ref().parlays.get(&parlay_id).cloned()
+    }
+
+    #[export]
+    pub fn query_parlays_by_user(&self, user: ActorId) -> Vec<Parlay> {
+        let state = SmartCupState::state_ref();
+        state
+            .user_parlays
+            .get(&user)
+            .map(|ids| ids.iter().filter_map(|id| state.parlays.get(id).cloned()).collect())
+            .unwrap_or_default()
+    }
+
+    #[export]
+    pub fn query_market(&self, market_id: u64) -> Option<Market> {
+        SmartCupState::state_ref().markets.get(&market_id).cloned()
+    }
+
+    #[export]
+    pub fn query_markets_by_match(&self, match_id: u64) -> Vec<Market> {
+        SmartCupState::state_ref()
+            .markets
+            .values()
+            .filter(|market| market.match_id == match_id)
+            .cloned()
+            .collect()
+    }
+
+    #[export]
+    pub fn query_market_bet(&self, market_id: u64, user: ActorId) -> Option<MarketBet> {
+        SmartCupState::state_ref().market_bets.get(&(user, market_id)).cloned()
+    }
+
+    #[export]
+    pub fn query_challenge(&self, challenge_id: u64) -> Option<Challenge> {
+        SmartCupState::state_ref().challenges.get(&challenge_id).cloned()
+    }
+
+    #[export]
+    pub fn query_challenges_by_user(&self, user: ActorId) -> Vec<Challenge> {
+        let state = SmartCupState::state_ref();
+        state
+            .user_challenges
+            .get(&user)
+            .map(|ids| ids.iter().filter_map(|id| state.challenges.get(id).cloned()).collect())
+            .unwrap_or_default()
+    }
+
+    #[export]
+    pub fn query_survivor_entry(&self, user: ActorId) -> Option<SurvivorEntry> {
+        SmartCupState::state_ref().survivor_entries.get(&user).cloned()
+    }
+
+    #[export]
+    pub fn query_survivor_status(&self) -> SurvivorStatus {
+        let state = SmartCupState::state_ref();
+        SurvivorStatus {
+            entry_fee: state.survivor_entry_fee,
+            pool: state.survivor_pool,
+            entrants: state.survivor_entrants.len() as u32,
+            alive: state.survivor_alive,
+            rounds: state.survivor_rounds.clone(),
+            finished: state.survivor_finished,
+            winners: state.survivor_winners,
+        }
+    }
+
+    #[export]
+    pub fn query_bracket(&self) -> Vec<BracketSlot> {
+        SmartCupState::state_ref().bracket_slots.clone()
+    }
+
+    #[export]
+    pub fn query_bracket_entry(&self, user: ActorId) -> Option<BracketEntry> {
+        SmartCupState::state_ref().bracket_entries.get(&user).cloned()
+    }
+
+    #[export]
+    pub fn query_jokers_remaining(&self, user: ActorId, phase: String) -> u32 {
+        SmartCupState::state_ref().jokers_remaining(user, &phase)
+    }
+
+    #[export]
+    pub fn query_group(&self, group: String) -> Option<Group> {
+        SmartCupState::state_ref().groups.get(&group).cloned()
+    }
+
+    /// Group table: the final one once the group is finalized, otherwise the live table
+    /// from the group matches finalized so far.
+    #[export]
+    pub fn query_group_table(&self, group: String) -> Vec<GroupStanding> {
+        let state = SmartCupState::state_ref();
+        match state.groups.get(&group) {
+            Some(g) if g.finalized => g.standings.clone(),
+            Some(g) => group_table(&g.teams, &state.group_results(g)),
+            None => Vec::new(),
+        }
+    }
+
+    #[export]
+    pub fn query_group_pick(&self, user: ActorId, group: String) -> Option<GroupPick> {
+        SmartCupState::state_ref().group_picks.get(&(user, group)).cloned()
+    }
+
+    /// Jackpot of the next match awaiting its result and the total still in play.
+    #[export]
+    pub fn query_jackpot(&self) -> JackpotStatus {
+        let state = SmartCupState::state_ref();
+
+        let next_match_id = state.next_pending_match(0, u64::MAX);
+        let next_jackpot = next_match_id
+            .and_then(|id| state.matches.get(&id))
+            .map(|m| m.jackpot)
+            .unwrap_or(0);
+        let total_pending = state
+            .matches
+            .values()
+            .filter(|m| matches!(m.result, ResultStatus::Unresolved | ResultStatus::Proposed { .. }))
+            .map(|m| m.jackpot)
+            .sum();
+
+        JackpotStatus {
+            next_match_id,
+            next_jackpot,
+            total_pending,
+            jackpot_bps: state.jackpot_bps,
+        }
+    }
+
+    /// Compares the program's native balance with its outstanding liabilities.
+    /// In token mode only the liabilities are reported, flagged with the token's id.
+    #[export]
+    pub fn query_solvency(&self) -> SolvencyReport {
+        let state = SmartCupState::state_ref();
+
+        let mut unclaimed_match_rewards: u128 = 0;
+        let mut unswept_match_pools: u128 = 0;
+        for m in state.matches.values() {
+            if m.dust_swept {
+                continue;
+            }
+            let outstanding = m
+                .match_prize_pool
+                .saturating_sub(m.total_claimed)
+                .saturating_add(m.jackpot.saturating_sub(m.jackpot_claimed));
+            if m.settlement_prepared {
+                unclaimed_match_rewards = unclaimed_match_rewards.saturating_add(outstanding);
+            } else {
+                unswept_match_pools = unswept_match_pools.saturating_add(outstanding);
+            }
+        }
+
+        let mut other_liabilities = state
+            .sponsor_pool
+            .saturating_add(state.parlay_pool)
+            .saturating_add(state.parlay_refunds_owed);
+        for pool in state.phase_prize_pools.values() {
+            other_liabilities = other_liabilities.saturating_add(*pool);
+        }
+        other_liabilities = other_liabilities
+            .saturating_add(state.challenge_escrow)
+            .saturating_add(state.survivor_pool);
+        for market in state.markets.values() {
+            other_liabilities = other_liabilities
+                .saturating_add(market.pool.saturating_sub(market.total_claimed));
+        }
+        for stats in state.referral_stats.values() {
+            other_liabilities = other_liabilities.saturating_add(stats.claimable);
+        }
+        for owed in state.failed_payouts.values() {
+            other_liabilities = other_liabilities.saturating_add(*owed);
+        }
+        for list in state.disputes.values() {
+            for d in list.iter().filter(|d| d.status == DisputeStatus::Open) {
+                other_liabilities = other_liabilities.saturating_add(d.bond);
+            }
+        }
+
+        let total_liabilities = unclaimed_match_rewards
+            .saturating_add(unswept_match_pools)
+            .saturating_add(state.final_prize_accumulated)
+            .saturating_add(state.final_prize_claimable_total)
+            .saturating_add(state.protocol_fee_accumulated)
+            .saturating_add(state.final_prize_rounding_dust)
+            .saturating_add(other_liabilities);
+
+        // Token balances live on the VFT program and cannot be read from a query
+        let balance = match state.payment_token {
+            Some(_) => 0,
+            None => exec::value_available(),
+        };
+        let (surplus, deficit) = match state.payment_token {
+            Some(_) => (0, 0),
+            None => (
+                balance.saturating_sub(total_liabilities),
+                total_liabilities.saturating_sub(balance),
+            ),
+        };
+
+        SolvencyReport {
+            payment_token: state.payment_token,
+            balance,
+            unclaimed_match_rewards,
+            unswept_match_pools,
+            final_prize_accumulated: state.final_prize_accumulated,
+            unclaimed_final_prize: state.final_prize_claimable_total,
+            protocol_fee_accumulated: state.protocol_fee_accumulated,
+            final_prize_rounding_dust: state.final_prize_rounding_dust,
+            other_liabilities,
+            total_liabilities,
+            surplus,
+            deficit,
+        }
+    }
+
+    #[export]
+    pub fn query_user_stats(&self, wallet: ActorId) -> UserStats {
+        SmartCupState::state_ref().user_stats(wallet)
+    }
+
+    /// Page of `wallet`'s bets in placement order, each with its outcome, points and payout.
+    #[export]
+    pub fn query_user_history(
+        &self,
+        wallet: ActorId,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<BetHistoryEntry> {
+        let state = SmartCupState::state_ref();
+
+        let records = match state.user_bets.get(&wallet) {
+            Some(v) => v,
+            None => return Vec::new(),
+        };
+
+        let mut out = Vec::new();
+        for r in records.iter().skip(offset as usize).take(limit as usize) {
+            let bet = match state.bets.get(&(wallet, r.match_id)) {
+                Some(b) => b,
+                None => continue,
+            };
+            let m = match state.matches.get(&r.match_id) {
+                Some(m) => m,
+                None => continue,
+            };
+            let phase_weight = state
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+
+            let (outcome, points, payout) = match m.result {
+                ResultStatus::Finalized { score, penalty_winner } => {
+                    let outcome = prediction_outcome(
+                        bet.score,
+                        bet.penalty_winner,
+                        score,
+                        penalty_winner,
+                        phase_weight,
+                    );
+                    let points = prediction_points(
+                        bet.score,
+                        bet.penalty_winner,
+                        score,
+                        penalty_winner,
+                        phase_weight,
+                    )
+                    .saturating_mul(bet.joker_multiplier);
+                    let eligible = eligible_for_payout(
+                        bet.score,
+                        bet.penalty_winner,
+                        score,
+                        penalty_winner,
+                        phase_weight,
+                    );
+                    let payout = if bet.claimed {
+                        bet.paid_out
+                    } else if eligible {
+                        let exact_hit = outcome == BetOutcome::ExactScore;
+                        let jackpot_amount = if exact_hit {
+                            jackpot_share(bet.stake_in_match_pool, m.jackpot, m.jackpot_winner_stake)
+                        } else {
+                            0
+                        };
+                        let stake = weighted_winner_stake(
+                            bet.stake_in_match_pool,
+                            exact_hit,
+                            state.exact_score_weight,
+                        );
+                        stake
+                            .saturating_mul(m.match_prize_pool)
+                            .checked_div(m.total_winner_stake)
+                            .unwrap_or(0)
+                            .saturating_add(jackpot_amount)
+                    } else {
+                        0
+                    };
+                    (outcome, points, payout)
+                }
+                ResultStatus::Expired => (BetOutcome::Expired, 0, bet.stake_in_match_pool),
+                _ => (BetOutcome::Pending, 0, 0),
+            };
+
+            out.push(BetHistoryEntry {
+                match_id: r.match_id,
+                phase: m.phase.clone(),
+                kick_off: m.kick_off,
+                score: bet.score,
+                penalty_winner: bet.penalty_winner,
+                stake_in_match_pool: bet.stake_in_match_pool,
+                outcome,
+                points,
+                payout,
+                claimed: bet.claimed,
+            });
+        }
+        out
+    }
+
     #[export]
     pub fn query_final_prize_claim_status(&self, wallet: ActorId) -> FinalPrizeClaimStatus {
         let state = SmartCupState::state_ref();
 
-        let points = state.user_points.get(&wallet).cloned().unwrap_or(0);
+        let points = state.total_points(wallet);
         let allocated = state
             .final_prize_allocations
             .get(&wallet)
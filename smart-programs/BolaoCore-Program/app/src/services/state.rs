@@ -2,7 +2,24 @@
 
 use sails_rs::{prelude::*, gstd::msg};
 use sails_rs::collections::HashMap as SailsHashMap;
-use super::types::{Match, PhaseConfig, Bet, UserBetRecord, PodiumPick, PodiumResult};
+use super::types::{
+    Match, PhaseConfig, Bet, UserBetRecord, PodiumPick, PodiumResult, ResultStatus,
+    Score, PenaltyWinner, Dispute, DisputeStatus, ReferralStats, FreePrediction,
+    BetOutcome, UserStats, PrizeCurve, Parlay, ParlayLeg, ParlayStatus, Market, MarketBet,
+    MarketSelection, MarketStatus, Challenge, ChallengeStatus, Prediction, SurvivorRound,
+    SurvivorEntry, BracketSlot, BracketEntry, Group, GroupPick,
+};
+use super::constants::{
+    DEFAULT_RESOLUTION_PERIOD_MS, DEFAULT_REFERRAL_FEE_BPS, MIN_BET_PLANCK,
+    BET_CLOSE_WINDOW_SECONDS, PROTOCOL_FEE_BPS, FINAL_PRIZE_BPS, BPS_DENOMINATOR,
+    DEFAULT_CHALLENGE_WINDOW_MS, DEFAULT_CLAIM_DEADLINE_MS, MIN_PARLAY_LEGS, MAX_PARLAY_LEGS,
+    MAX_GROUP_MATCHES, DEFAULT_JOKERS_PER_PHASE, DEFAULT_JOKER_MULTIPLIER,
+    DEFAULT_EXACT_SCORE_WEIGHT,
+};
+use super::utils::{
+    prediction_points, prediction_outcome, payout_side, is_knockout, eligible_for_payout,
+    market_selections, outcome, advance_outcome, group_table, group_bonus, streak_bonus,
+};
 
 pub static mut SMARTCUP_STATE: Option<SmartCupState> = None;
 
@@ -20,6 +37,9 @@ pub struct SmartCupState {
     pub podium_picks: SailsHashMap<ActorId, PodiumPick>,
     pub podium_result: Option<PodiumResult>,
     pub podium_finalized: bool,
+    /// Final and third-place match the podium is derived from.
+    pub final_match_id: Option<u64>,
+    pub third_place_match_id: Option<u64>,
     pub r32_lock_time: Option<u64>,
     pub authorized_oracles: SailsHashMap<ActorId, bool>,
     pub final_prize_finalized: bool,
@@ -27,6 +47,118 @@ pub struct SmartCupState {
     pub final_prize_rounding_dust: u128,
     pub final_prize_allocations: SailsHashMap<ActorId, u128>,
     pub final_prize_claimed: SailsHashMap<ActorId, bool>,
+    /// Match-pool stake per (match_id, payout side) — side as returned by `payout_side`.
+    pub side_stake: SailsHashMap<(u64, i8), u128>,
+    /// Match-pool stake per (match_id, exact predicted score, penalty winner).
+    pub exact_score_stake: SailsHashMap<(u64, Score, Option<PenaltyWinner>), u128>,
+    /// Period after kick-off applied to newly registered matches as their resolution deadline.
+    pub resolution_period_ms: u64,
+    /// Disputes per match, in the order they were opened; the index is the dispute id.
+    pub disputes: SailsHashMap<u64, Vec<Dispute>>,
+    /// DAO program allowed to arbitrate disputes alongside admins.
+    pub dao_program: Option<ActorId>,
+    /// Share of the protocol fee (in BPS of the fee) paid to referrers.
+    pub referral_fee_bps: u128,
+    pub referral_codes: SailsHashMap<String, ActorId>,
+    pub referred_by: SailsHashMap<ActorId, ActorId>,
+    pub referral_stats: SailsHashMap<ActorId, ReferralStats>,
+    /// VFT program bets and payouts are denominated in; `None` means native VARA.
+    pub payment_token: Option<ActorId>,
+    /// Outbound transfers that failed (claims, refunds, prizes, bonds and withdrawals),
+    /// owed per recipient and retryable with `retry_failed_payout`.
+    pub failed_payouts: SailsHashMap<ActorId, u128>,
+    pub free_predictions: SailsHashMap<(ActorId, u64), FreePrediction>,
+    /// Match ids each wallet made a free prediction on, in order.
+    pub user_free_predictions: SailsHashMap<ActorId, Vec<u64>>,
+    /// Materialized free-to-play leaderboard points.
+    pub free_points: SailsHashMap<ActorId, u32>,
+    /// Sponsor-funded prize for the free-to-play leaderboard.
+    pub sponsor_pool: u128,
+    pub free_prize_finalized: bool,
+    pub free_prize_allocations: SailsHashMap<ActorId, u128>,
+    pub free_prize_claimed: SailsHashMap<ActorId, bool>,
+    /// Profile counters, updated on bet, points crediting and claim.
+    pub user_stats: SailsHashMap<ActorId, UserStats>,
+    /// Split used by the final prize and the free-to-play prize.
+    pub prize_curve: PrizeCurve,
+    /// Slice of every bet (in BPS of the bet, carved out of the final prize cut) that funds
+    /// the prize of the bet's phase.
+    pub phase_prize_bps: u128,
+    /// Phase prize per phase; after finalization, the part not yet claimed.
+    pub phase_prize_pools: SailsHashMap<String, u128>,
+    /// Materialized points per (wallet, phase).
+    pub phase_points: SailsHashMap<(ActorId, String), u32>,
+    pub phase_prize_finalized: SailsHashMap<String, bool>,
+    pub phase_prize_allocations: SailsHashMap<(String, ActorId), u128>,
+    pub phase_prize_claimed: SailsHashMap<(String, ActorId), bool>,
+    /// Block timestamp of each phase prize's finalization; claims close after the
+    /// phase's claim deadline and the unclaimed rest can be swept.
+    pub phase_prize_finalized_at: SailsHashMap<String, u64>,
+    /// Slice of every bet (in BPS of the bet, carved out of the match pool cut) that funds
+    /// the match's exact-score jackpot.
+    pub jackpot_bps: u128,
+    pub parlays: SailsHashMap<u64, Parlay>,
+    pub next_parlay_id: u64,
+    pub user_parlays: SailsHashMap<ActorId, Vec<u64>>,
+    /// Pool shared by winning parlays; after finalization, the part not yet claimed.
+    pub parlay_pool: u128,
+    /// Pool stakes of voided parlays, moved out of `parlay_pool` and owed back to their owners.
+    pub parlay_refunds_owed: u128,
+    /// Sum of stake × live legs over winning parlays.
+    pub parlay_winning_weight: u128,
+    /// Parlays not settled yet.
+    pub parlays_open: u32,
+    pub parlay_pool_finalized: bool,
+    /// `parlay_pool` when it was finalized; every winning share is taken from it.
+    pub parlay_payout_pool: u128,
+    pub markets: SailsHashMap<u64, Market>,
+    pub next_market_id: u64,
+    pub market_bets: SailsHashMap<(ActorId, u64), MarketBet>,
+    pub challenges: SailsHashMap<u64, Challenge>,
+    pub next_challenge_id: u64,
+    /// Challenge ids each wallet created or accepted.
+    pub user_challenges: SailsHashMap<ActorId, Vec<u64>>,
+    /// Stakes held for open and accepted challenges.
+    pub challenge_escrow: u128,
+    pub survivor_entry_fee: u128,
+    /// Entrants in join order; the round settlement cursor runs over it.
+    pub survivor_entrants: Vec<ActorId>,
+    pub survivor_entries: SailsHashMap<ActorId, SurvivorEntry>,
+    pub survivor_rounds: Vec<SurvivorRound>,
+    /// Entry pool; after the game ends, the part not yet claimed.
+    pub survivor_pool: u128,
+    pub survivor_alive: u32,
+    pub survivor_finished: bool,
+    pub survivor_winners: u32,
+    /// Round that eliminated every remaining entrant; they share the pool.
+    pub survivor_wipeout_round: Option<u32>,
+    /// `survivor_pool` when the game ended; every winning share is taken from it.
+    pub survivor_payout_pool: u128,
+    pub bracket_slots: Vec<BracketSlot>,
+    pub bracket_entries: SailsHashMap<ActorId, BracketEntry>,
+    /// Bracket submitters in order; the slot scoring cursor runs over it.
+    pub bracket_entrants: Vec<ActorId>,
+    pub groups: SailsHashMap<String, Group>,
+    pub group_picks: SailsHashMap<(ActorId, String), GroupPick>,
+    /// Wallets that picked each group's placements, in order; the scoring cursor runs over it.
+    pub group_pickers: SailsHashMap<String, Vec<ActorId>>,
+    pub jokers_per_phase: u32,
+    pub joker_multiplier: u32,
+    /// Jokers used per (wallet, phase).
+    pub jokers_used: SailsHashMap<(ActorId, String), u32>,
+    /// `(run length, points)` in increasing run length; empty = no streak bonus.
+    pub streak_thresholds: Vec<(u32, u32)>,
+    /// Bets placed at least this long before kick-off earn `early_bird_points`.
+    pub early_bird_lead_ms: u64,
+    pub early_bird_points: u32,
+    /// Wallets with a bet in each phase, in order; the phase bonus cursor runs over it.
+    pub phase_bettors: SailsHashMap<String, Vec<ActorId>>,
+    pub phase_bonus_cursor: SailsHashMap<String, u32>,
+    pub phase_bonus_settled: SailsHashMap<String, bool>,
+    /// Streak and early-bird bonus credited per (wallet, phase).
+    pub phase_bonus_paid: SailsHashMap<(ActorId, String), (u32, u32)>,
+    /// Times an exact-score winner's stake counts in the match reward split.
+    pub exact_score_weight: u32,
 }
 
 impl SmartCupState {
@@ -34,6 +166,12 @@ impl SmartCupState {
         unsafe {
             SMARTCUP_STATE = Some(Self {
                 admins: vec![admin],
+                resolution_period_ms: DEFAULT_RESOLUTION_PERIOD_MS,
+                referral_fee_bps: DEFAULT_REFERRAL_FEE_BPS,
+                survivor_entry_fee: MIN_BET_PLANCK,
+                jokers_per_phase: DEFAULT_JOKERS_PER_PHASE,
+                joker_multiplier: DEFAULT_JOKER_MULTIPLIER,
+                exact_score_weight: DEFAULT_EXACT_SCORE_WEIGHT,
                 ..Default::default()
             })
         }
@@ -59,6 +197,14 @@ impl SmartCupState {
         }
     }
 
+    /// Panics if the caller is neither an admin nor the configured DAO program.
+    pub fn only_arbiter(&self) {
+        let caller = msg::source();
+        if !self.admins.contains(&caller) && self.dao_program != Some(caller) {
+            panic!("Only admin or DAO");
+        }
+    }
+
     /// Panics if the caller is not an active authorized oracle.
     pub fn only_oracle(&self) {
         let caller = msg::source();
@@ -66,6 +212,1599 @@ impl SmartCupState {
             panic!("Only authorized oracle");
         }
     }
+
+    /// Challenge window of the match's phase, or the default if the phase is unknown.
+    pub fn challenge_window_ms(&self, m: &Match) -> u64 {
+        self.phases
+            .get(&m.phase)
+            .map(|p| p.challenge_window_ms)
+            .unwrap_or(DEFAULT_CHALLENGE_WINDOW_MS)
+    }
+
+    /// Claim deadline of the match's phase, or the default if the phase is unknown.
+    pub fn claim_deadline_ms(&self, m: &Match) -> u64 {
+        self.phase_claim_deadline_ms(&m.phase)
+    }
+
+    /// Claim deadline of `phase`, or the default if the phase is unknown.
+    pub fn phase_claim_deadline_ms(&self, phase: &str) -> u64 {
+        self.phases
+            .get(phase)
+            .map(|p| p.claim_deadline_ms)
+            .unwrap_or(DEFAULT_CLAIM_DEADLINE_MS)
+    }
+
+    /// Marks every open dispute on `match_id` as upheld and clears the match's freeze.
+    /// Returns (dispute index, disputer, bond) for each bond to refund.
+    pub fn uphold_open_disputes(&mut self, match_id: u64) -> Vec<(u32, ActorId, u128)> {
+        let mut refunds = Vec::new();
+        if let Some(list) = self.disputes.get_mut(&match_id) {
+            for (index, d) in list.iter_mut().enumerate() {
+                if d.status == DisputeStatus::Open {
+                    d.status = DisputeStatus::Upheld;
+                    refunds.push((index as u32, d.disputer, d.bond));
+                }
+            }
+        }
+        if let Some(m) = self.matches.get_mut(&match_id) {
+            m.open_disputes = 0;
+        }
+        refunds
+    }
+
+    /// Next match still awaiting its result in kick-off order after `kick_off`, falling back
+    /// to the earliest pending match if none kicks off later.
+    pub fn next_pending_match(&self, kick_off: u64, exclude: u64) -> Option<u64> {
+        self.matches
+            .values()
+            .filter(|m| m.match_id != exclude)
+            .filter(|m| matches!(m.result, ResultStatus::Unresolved | ResultStatus::Proposed { .. }))
+            .min_by_key(|m| (m.kick_off < kick_off, m.kick_off, m.match_id))
+            .map(|m| m.match_id)
+    }
+
+    /// Moves the jackpot of `match_id` to the next pending match (or the final prize pool
+    /// when none is left). Returns the receiving match and the amount, if there was any.
+    pub fn roll_over_jackpot(&mut self, match_id: u64) -> Option<(Option<u64>, u128)> {
+        let (amount, kick_off) = {
+            let m = self.matches.get_mut(&match_id)?;
+            if m.jackpot == 0 {
+                return None;
+            }
+            let amount = m.jackpot;
+            m.jackpot = 0;
+            (amount, m.kick_off)
+        };
+
+        let next = self.next_pending_match(kick_off, match_id);
+        match next.and_then(|id| self.matches.get_mut(&id)) {
+            Some(n) => n.jackpot = n.jackpot.saturating_add(amount),
+            None => {
+                self.final_prize_accumulated = self.final_prize_accumulated.saturating_add(amount)
+            }
+        }
+        Some((next, amount))
+    }
+
+    /// Books the protocol fee of a stake of `amount` (minus the referrer's share, which
+    /// accrues to the referrer). Returns the final prize cut, not yet booked, and what is
+    /// left for the product's own pool.
+    pub fn take_protocol_fee(&mut self, bettor: ActorId, amount: u128) -> (u128, u128) {
+        let protocol_fee = amount.saturating_mul(PROTOCOL_FEE_BPS) / BPS_DENOMINATOR;
+        let final_prize_cut = amount.saturating_mul(FINAL_PRIZE_BPS) / BPS_DENOMINATOR;
+
+        // Referred bettors share part of the protocol fee with their referrer
+        let mut referral_cut: u128 = 0;
+        if let Some(referrer) = self.referred_by.get(&bettor) {
+            referral_cut = protocol_fee.saturating_mul(self.referral_fee_bps) / BPS_DENOMINATOR;
+            let stats = self.referral_stats.entry(*referrer).or_default();
+            stats.volume = stats.volume.saturating_add(amount);
+            stats.earned = stats.earned.saturating_add(referral_cut);
+            stats.claimable = stats.claimable.saturating_add(referral_cut);
+        }
+
+        self.protocol_fee_accumulated = self
+            .protocol_fee_accumulated
+            .saturating_add(protocol_fee.saturating_sub(referral_cut));
+
+        let rest = amount
+            .saturating_sub(protocol_fee)
+            .saturating_sub(final_prize_cut);
+        (final_prize_cut, rest)
+    }
+
+    /// Validates a parlay without touching state. Returns the rejection reason, if any.
+    pub fn check_parlay(
+        &self,
+        legs: &[ParlayLeg],
+        amount: u128,
+        now: u64,
+    ) -> Result<(), &'static str> {
+        if self.parlay_pool_finalized {
+            return Err("Parlay pool already finalized");
+        }
+        if legs.len() < MIN_PARLAY_LEGS {
+            return Err("Too few parlay legs");
+        }
+        if legs.len() > MAX_PARLAY_LEGS {
+            return Err("Too many parlay legs");
+        }
+        if amount < MIN_BET_PLANCK {
+            return Err("Bet below minimum");
+        }
+        for (i, leg) in legs.iter().enumerate() {
+            if legs[..i].iter().any(|other| other.match_id == leg.match_id) {
+                return Err("Duplicate parlay leg");
+            }
+            self.check_prediction(leg.match_id, leg.score, leg.penalty_winner, now)?;
+        }
+        Ok(())
+    }
+
+    /// Books a parlay already validated by `check_parlay`: protocol fee and final prize
+    /// cut as for a single bet, the rest into the parlay pool.
+    /// Returns the new parlay id and its stake in the pool.
+    pub fn record_parlay(
+        &mut self,
+        owner: ActorId,
+        legs: Vec<ParlayLeg>,
+        amount: u128,
+    ) -> (u64, u128) {
+        let (final_prize_cut, pool_cut) = self.take_protocol_fee(owner, amount);
+        self.final_prize_accumulated =
+            self.final_prize_accumulated.saturating_add(final_prize_cut);
+        self.parlay_pool = self.parlay_pool.saturating_add(pool_cut);
+
+        let parlay_id = self.next_parlay_id;
+        self.next_parlay_id = self.next_parlay_id.saturating_add(1);
+        self.parlays.insert(
+            parlay_id,
+            Parlay {
+                parlay_id,
+                owner,
+                legs,
+                stake_in_pool: pool_cut,
+                status: ParlayStatus::Open,
+                live_legs: 0,
+                claimed: false,
+                paid_out: 0,
+            },
+        );
+        self.parlays_open = self.parlays_open.saturating_add(1);
+        self.user_parlays.entry(owner).or_default().push(parlay_id);
+
+        let stats = self.user_stats.entry(owner).or_default();
+        stats.total_staked = stats.total_staked.saturating_add(amount);
+
+        (parlay_id, pool_cut)
+    }
+
+    /// Decides a parlay once every leg's match is finalized or expired; `None` while any
+    /// leg is still open. Legs on expired matches are dropped; the parlay wins if every
+    /// remaining leg is eligible for payout, and is void if none remains.
+    /// Returns the status and the number of live legs.
+    pub fn decide_parlay(&self, legs: &[ParlayLeg]) -> Option<(ParlayStatus, u32)> {
+        let mut live_legs: u32 = 0;
+        let mut all_won = true;
+        for leg in legs.iter() {
+            let m = self.matches.get(&leg.match_id)?;
+            let (final_score, final_penalty_winner) = match m.result {
+                ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
+                ResultStatus::Expired => continue,
+                _ => return None,
+            };
+            let phase_weight = self
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+
+            live_legs = live_legs.saturating_add(1);
+            all_won &= eligible_for_payout(
+                leg.score,
+                leg.penalty_winner,
+                final_score,
+                final_penalty_winner,
+                phase_weight,
+            );
+        }
+
+        let status = if live_legs == 0 {
+            ParlayStatus::Void
+        } else if all_won {
+            ParlayStatus::Won
+        } else {
+            ParlayStatus::Lost
+        };
+        Some((status, live_legs))
+    }
+
+    /// Panics unless `match_id` is a knockout match not yet tied to a bracket slot.
+    pub fn check_bracket_match(&self, match_id: u64) {
+        let m = self.matches.get(&match_id).expect("Match not found");
+        let phase_weight = self
+            .phases
+            .get(&m.phase)
+            .map(|p| p.points_weight)
+            .unwrap_or(1);
+        if !is_knockout(phase_weight) {
+            panic!("Bracket slots need knockout matches");
+        }
+        if !matches!(m.result, ResultStatus::Unresolved | ResultStatus::Proposed { .. }) {
+            panic!("Match already settled");
+        }
+        if self.bracket_slots.iter().any(|s| s.match_id == Some(match_id)) {
+            panic!("Match already in bracket");
+        }
+    }
+
+    /// Validates a bracket: one pick per slot, a first-round pick must play the slot's
+    /// match and a later pick must be one of the user's own picks for its feeder slots.
+    pub fn check_bracket(&self, picks: &[String]) -> Result<(), &'static str> {
+        if self.bracket_slots.is_empty() {
+            return Err("No bracket registered");
+        }
+        if picks.len() != self.bracket_slots.len() {
+            return Err("One pick per bracket slot required");
+        }
+        for (slot, pick) in self.bracket_slots.iter().zip(picks.iter()) {
+            let valid = match slot.feeders {
+                Some((a, b)) => *pick == picks[a as usize] || *pick == picks[b as usize],
+                None => slot
+                    .match_id
+                    .and_then(|id| self.matches.get(&id))
+                    .is_some_and(|m| !pick.is_empty() && (m.home == *pick || m.away == *pick)),
+            };
+            if !valid {
+                return Err("Pick cannot advance from this slot");
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the advancing team of the bracket slot tied to `match_id`, if any, from its
+    /// finalized result. Returns the slot, the team and the points a correct pick earns.
+    pub fn decide_bracket_slot(&mut self, match_id: u64) -> Option<(u32, String, u32)> {
+        let m = self.matches.get(&match_id)?;
+        let team = match m.result {
+            ResultStatus::Finalized { score, penalty_winner } => {
+                if score.home == score.away && penalty_winner.is_none() {
+                    return None;
+                }
+                if advance_outcome(score, penalty_winner) > 0 {
+                    m.home.clone()
+                } else {
+                    m.away.clone()
+                }
+            }
+            _ => return None,
+        };
+        let points = self
+            .phases
+            .get(&m.phase)
+            .map(|p| p.points_weight)
+            .unwrap_or(1);
+
+        let no_entrants = self.bracket_entrants.is_empty();
+        let slot = self
+            .bracket_slots
+            .iter_mut()
+            .find(|s| s.match_id == Some(match_id) && !s.scored)?;
+        slot.winner = Some(team.clone());
+        slot.points = points;
+        slot.scored = no_entrants;
+        Some((slot.slot_id, team, points))
+    }
+
+    /// Voids the bracket slot tied to an expired match: nobody scores it.
+    pub fn void_bracket_slot(&mut self, match_id: u64) -> Option<u32> {
+        let slot = self
+            .bracket_slots
+            .iter_mut()
+            .find(|s| s.match_id == Some(match_id) && !s.scored)?;
+        slot.scored = true;
+        Some(slot.slot_id)
+    }
+
+    /// Credits up to `max_items` bracket entrants from a decided slot's cursor.
+    /// Returns the (user, points) credited and whether the slot is fully scored.
+    pub fn score_bracket_batch(
+        &mut self,
+        slot_id: u32,
+        max_items: u32,
+    ) -> (Vec<(ActorId, u32)>, bool) {
+        let slot = self
+            .bracket_slots
+            .get(slot_id as usize)
+            .cloned()
+            .expect("No such slot");
+        if slot.scored {
+            panic!("Slot already scored");
+        }
+        let winner = slot.winner.expect("Slot not decided");
+
+        let start = slot.scoring_cursor as usize;
+        let end = start
+            .saturating_add(max_items as usize)
+            .min(self.bracket_entrants.len());
+
+        let mut awarded: Vec<(ActorId, u32)> = Vec::new();
+        for i in start..end {
+            let user = self.bracket_entrants[i];
+            let entry = match self.bracket_entries.get_mut(&user) {
+                Some(e) => e,
+                None => continue,
+            };
+            if entry.picks[slot_id as usize] != winner {
+                continue;
+            }
+            entry.points = entry.points.saturating_add(slot.points);
+            let pts = self.user_points.entry(user).or_insert(0);
+            *pts = pts.saturating_add(slot.points);
+            awarded.push((user, slot.points));
+        }
+
+        let slot = &mut self.bracket_slots[slot_id as usize];
+        slot.scoring_cursor = end as u32;
+        slot.scored = end >= self.bracket_entrants.len();
+        (awarded, slot.scored)
+    }
+
+    /// Bracket points `user` has earned on decided slots the scoring crank has not
+    /// reached yet.
+    pub fn pending_bracket_points(&self, user: ActorId) -> u32 {
+        let entry = match self.bracket_entries.get(&user) {
+            Some(e) => e,
+            None => return 0,
+        };
+        self.bracket_slots
+            .iter()
+            .filter(|s| !s.scored && entry.index >= s.scoring_cursor)
+            .filter(|s| s.winner.as_ref() == entry.picks.get(s.slot_id as usize))
+            .map(|s| s.points)
+            .fold(0u32, |acc, p| acc.saturating_add(p))
+    }
+
+    /// Panics unless `match_id` can take over a podium slot now held by `current`: a
+    /// designated match is only replaced once it expired, and the new one must be an
+    /// unresolved knockout match. Keeping the current designation always passes.
+    pub fn check_podium_match(&self, current: Option<u64>, match_id: u64) {
+        if current == Some(match_id) {
+            return;
+        }
+        if let Some(current_id) = current {
+            let expired = self
+                .matches
+                .get(&current_id)
+                .map(|m| m.result == ResultStatus::Expired)
+                .unwrap_or(false);
+            if !expired {
+                panic!("Podium match can only be replaced once it expired");
+            }
+        }
+        let m = self.matches.get(&match_id).expect("Match not found");
+        let phase_weight = self
+            .phases
+            .get(&m.phase)
+            .map(|p| p.points_weight)
+            .unwrap_or(1);
+        if !is_knockout(phase_weight) {
+            panic!("Podium matches must be knockout matches");
+        }
+        if m.result != ResultStatus::Unresolved {
+            panic!("Podium matches must be unresolved");
+        }
+    }
+
+    /// Winner and loser of a finalized knockout match, penalties included.
+    pub fn knockout_result(&self, match_id: u64) -> Result<(String, String), &'static str> {
+        let m = self.matches.get(&match_id).ok_or("Match not found")?;
+        match m.result {
+            ResultStatus::Finalized { score, penalty_winner } => {
+                if score.home == score.away && penalty_winner.is_none() {
+                    return Err("Knockout draw without penalty winner");
+                }
+                if advance_outcome(score, penalty_winner) > 0 {
+                    Ok((m.home.clone(), m.away.clone()))
+                } else {
+                    Ok((m.away.clone(), m.home.clone()))
+                }
+            }
+            _ => Err("Podium matches not finalized"),
+        }
+    }
+
+    /// Podium from the finalized Final (champion, runner-up) and third-place match.
+    pub fn podium_from_matches(&self) -> Result<PodiumResult, &'static str> {
+        let final_id = self.final_match_id.ok_or("Podium matches not set")?;
+        let third_id = self.third_place_match_id.ok_or("Podium matches not set")?;
+        let (champion, runner_up) = self.knockout_result(final_id)?;
+        let (third_place, _) = self.knockout_result(third_id)?;
+        Ok(PodiumResult { champion, runner_up, third_place })
+    }
+
+    /// Adds a match with a fresh id and moves the podium lock to the earliest Round of 32
+    /// kick-off. Returns the match id.
+    pub fn insert_match(
+        &mut self,
+        phase: String,
+        home: String,
+        away: String,
+        kick_off: u64,
+        feeders: Option<(u64, u64)>,
+    ) -> u64 {
+        let match_id = self.next_match_id.saturating_add(1);
+        self.next_match_id = match_id;
+
+        if phase == "Round of 32" {
+            match self.r32_lock_time {
+                None => self.r32_lock_time = Some(kick_off),
+                Some(t) => {
+                    if kick_off < t {
+                        self.r32_lock_time = Some(kick_off);
+                    }
+                }
+            }
+        }
+
+        let m = Match {
+            match_id,
+            phase,
+            home,
+            away,
+            kick_off,
+            resolution_deadline: kick_off.saturating_add(self.resolution_period_ms),
+            result: ResultStatus::Unresolved,
+            open_disputes: 0,
+            match_prize_pool: 0,
+            has_bets: false,
+            participants: Vec::new(),
+            free_participants: Vec::new(),
+            total_winner_stake: 0,
+            claimed_winner_stake: 0,
+            total_claimed: 0,
+            settlement_prepared: false,
+            settlement_cursor: 0,
+            points_settled: false,
+            free_settlement_cursor: 0,
+            free_points_settled: false,
+            dust_swept: false,
+            finalized_at: None,
+            expired_at: None,
+            jackpot: 0,
+            jackpot_winner_stake: 0,
+            jackpot_claimed: 0,
+            feeders,
+        };
+        self.matches.insert(match_id, m);
+        match_id
+    }
+
+    /// Validates the feeders of a placeholder match kicking off at `kick_off`: two distinct
+    /// knockout matches, not expired, kicking off earlier and feeding no other match.
+    pub fn check_feeders(&self, feeders: (u64, u64), kick_off: u64) -> Result<(), &'static str> {
+        if feeders.0 == feeders.1 {
+            return Err("Feeders must be two distinct matches");
+        }
+        for feeder_id in [feeders.0, feeders.1] {
+            let m = self.matches.get(&feeder_id).ok_or("Feeder match not found")?;
+            let phase_weight = self
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+            if !is_knockout(phase_weight) {
+                return Err("Feeders must be knockout matches");
+            }
+            if m.result == ResultStatus::Expired {
+                return Err("Feeder match expired");
+            }
+            if m.kick_off >= kick_off {
+                return Err("Feeder must kick off earlier");
+            }
+            let taken = self.matches.values().any(|other| {
+                other.feeders.is_some_and(|(h, a)| h == feeder_id || a == feeder_id)
+            });
+            if taken {
+                return Err("Feeder already feeds a match");
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the winner of knockout match `feeder_id` into the placeholder match it feeds,
+    /// once the feeder is finalized. Returns the placeholder's id.
+    pub fn advance_winner(&mut self, feeder_id: u64) -> Option<u64> {
+        let (winner, _) = self.knockout_result(feeder_id).ok()?;
+        let m = self
+            .matches
+            .values_mut()
+            .find(|m| m.feeders.is_some_and(|(h, a)| h == feeder_id || a == feeder_id))?;
+        let (home_from, _) = m.feeders?;
+        if home_from == feeder_id {
+            m.home = winner;
+        } else {
+            m.away = winner;
+        }
+        Some(m.match_id)
+    }
+
+    /// Jokers `user` can still apply in `phase`.
+    pub fn jokers_remaining(&self, user: ActorId, phase: &str) -> u32 {
+        let used = self
+            .jokers_used
+            .get(&(user, phase.to_string()))
+            .cloned()
+            .unwrap_or(0);
+        self.jokers_per_phase.saturating_sub(used)
+    }
+
+    /// Whether streak or early-bird bonuses are configured.
+    pub fn phase_bonuses_enabled(&self) -> bool {
+        !self.streak_thresholds.is_empty() || self.early_bird_points > 0
+    }
+
+    /// Whether every match of `phase` is finalized or expired.
+    pub fn phase_complete(&self, phase: &str) -> bool {
+        self.matches
+            .values()
+            .filter(|m| m.phase == phase)
+            .all(|m| matches!(m.result, ResultStatus::Finalized { .. } | ResultStatus::Expired))
+    }
+
+    /// Streak and early-bird bonus `user` earns over the finalized matches of `phase`.
+    /// Streaks run in kick-off order, whatever order the results were finalized in;
+    /// expired matches neither extend nor break them.
+    pub fn phase_bonuses(&self, user: ActorId, phase: &str) -> (u32, u32) {
+        let records = match self.user_bets.get(&user) {
+            Some(v) => v,
+            None => return (0, 0),
+        };
+
+        let mut decided: Vec<(u64, bool)> = Vec::new();
+        let mut early_bird: u32 = 0;
+        for r in records.iter() {
+            let bet = match self.bets.get(&(user, r.match_id)) {
+                Some(b) => b,
+                None => continue,
+            };
+            let m = match self.matches.get(&r.match_id) {
+                Some(m) if m.phase == phase => m,
+                _ => continue,
+            };
+            let (final_score, final_penalty_winner) = match m.result {
+                ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
+                _ => continue,
+            };
+            let phase_weight = self
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+
+            let outcome = prediction_outcome(
+                bet.score,
+                bet.penalty_winner,
+                final_score,
+                final_penalty_winner,
+                phase_weight,
+            );
+            decided.push((m.kick_off, outcome != BetOutcome::Miss));
+            if m.kick_off.saturating_sub(bet.placed_at) >= self.early_bird_lead_ms {
+                early_bird = early_bird.saturating_add(self.early_bird_points);
+            }
+        }
+
+        decided.sort_by_key(|(kick_off, _)| *kick_off);
+        let scored: Vec<bool> = decided.into_iter().map(|(_, hit)| hit).collect();
+        (streak_bonus(&scored, &self.streak_thresholds), early_bird)
+    }
+
+    /// Streak and early-bird bonus `user` has earned in completed phases the bonus crank
+    /// has not reached yet.
+    pub fn pending_phase_bonuses(&self, user: ActorId) -> (u32, u32) {
+        if !self.phase_bonuses_enabled() {
+            return (0, 0);
+        }
+        let mut pending = (0u32, 0u32);
+        for phase in self.phases.keys() {
+            if self.phase_bonus_settled.get(phase).cloned().unwrap_or(false)
+                || self.phase_bonus_paid.contains_key(&(user, phase.clone()))
+                || !self.phase_complete(phase)
+            {
+                continue;
+            }
+            let (streak, early_bird) = self.phase_bonuses(user, phase);
+            pending.0 = pending.0.saturating_add(streak);
+            pending.1 = pending.1.saturating_add(early_bird);
+        }
+        pending
+    }
+
+    /// Credits streak and early-bird bonuses to up to `max_items` bettors of a completed
+    /// phase from its cursor. Returns the (user, streak, early bird) credited and whether
+    /// the phase is fully settled.
+    pub fn settle_phase_bonus_batch(
+        &mut self,
+        phase: &str,
+        max_items: u32,
+    ) -> (Vec<(ActorId, u32, u32)>, bool) {
+        if !self.phase_bonuses_enabled() {
+            panic!("No phase bonuses configured");
+        }
+        if self.phase_bonus_settled.get(phase).cloned().unwrap_or(false) {
+            panic!("Phase bonuses already settled");
+        }
+        if !self.phase_complete(phase) {
+            panic!("Not all phase matches settled");
+        }
+        let bettors = self.phase_bettors.get(phase).cloned().unwrap_or_default();
+
+        let start = self.phase_bonus_cursor.get(phase).cloned().unwrap_or(0) as usize;
+        let end = start.saturating_add(max_items as usize).min(bettors.len());
+
+        let mut awarded: Vec<(ActorId, u32, u32)> = Vec::new();
+        for user in bettors[start..end].iter() {
+            let (streak, early_bird) = self.phase_bonuses(*user, phase);
+            self.phase_bonus_paid.insert((*user, phase.to_string()), (streak, early_bird));
+            let bonus = streak.saturating_add(early_bird);
+            if bonus == 0 {
+                continue;
+            }
+            let pts = self.user_points.entry(*user).or_insert(0);
+            *pts = pts.saturating_add(bonus);
+            let pts = self.phase_points.entry((*user, phase.to_string())).or_insert(0);
+            *pts = pts.saturating_add(bonus);
+            let stats = self.user_stats.entry(*user).or_default();
+            stats.streak_bonus = stats.streak_bonus.saturating_add(streak);
+            stats.early_bird_bonus = stats.early_bird_bonus.saturating_add(early_bird);
+            awarded.push((*user, streak, early_bird));
+        }
+
+        let settled = end >= bettors.len();
+        self.phase_bonus_cursor.insert(phase.to_string(), end as u32);
+        self.phase_bonus_settled.insert(phase.to_string(), settled);
+        (awarded, settled)
+    }
+
+    /// Validates the matches of a new group: group-stage matches still open for betting
+    /// and in no other group. Returns the group's teams in order of first appearance.
+    pub fn check_group(
+        &self,
+        match_ids: &[u64],
+        now: u64,
+    ) -> Result<Vec<String>, &'static str> {
+        if match_ids.is_empty() || match_ids.len() > MAX_GROUP_MATCHES {
+            return Err("Invalid group size");
+        }
+        let mut teams: Vec<String> = Vec::new();
+        for (i, match_id) in match_ids.iter().enumerate() {
+            if match_ids[..i].contains(match_id) {
+                return Err("Duplicate match");
+            }
+            let m = self.matches.get(match_id).ok_or("Match not found")?;
+            let phase_weight = self
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+            if is_knockout(phase_weight) {
+                return Err("Groups need group-stage matches");
+            }
+            if now >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS) {
+                return Err("Group match betting closed");
+            }
+            if self.groups.values().any(|g| g.match_ids.contains(match_id)) {
+                return Err("Match already in a group");
+            }
+            for team in [&m.home, &m.away] {
+                if !teams.contains(team) {
+                    teams.push(team.clone());
+                }
+            }
+        }
+        if teams.len() < 3 {
+            return Err("Group needs at least three teams");
+        }
+        Ok(teams)
+    }
+
+    /// Whether betting has closed on any match of `group`, which locks its picks.
+    pub fn group_started(&self, group: &Group, now: u64) -> bool {
+        group.match_ids.iter().filter_map(|id| self.matches.get(id)).any(|m| {
+            now >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS)
+        })
+    }
+
+    /// `(home, away, score)` of every finalized match of `group`.
+    pub fn group_results(&self, group: &Group) -> Vec<(String, String, Score)> {
+        group
+            .match_ids
+            .iter()
+            .filter_map(|id| self.matches.get(id))
+            .filter_map(|m| match m.result {
+                ResultStatus::Finalized { score, .. } => {
+                    Some((m.home.clone(), m.away.clone(), score))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Fixes the table of a group whose matches are all finalized or expired. An expired
+    /// match voids the group. Returns the teams in table order, or `None` when voided.
+    pub fn finalize_group(&mut self, name: &str) -> Option<Vec<String>> {
+        let group = self.groups.get(name).cloned().expect("No such group");
+        if group.finalized {
+            panic!("Group already finalized");
+        }
+        let mut void = false;
+        for match_id in group.match_ids.iter() {
+            match self.matches.get(match_id).map(|m| &m.result) {
+                Some(ResultStatus::Finalized { .. }) => {}
+                Some(ResultStatus::Expired) => void = true,
+                _ => panic!("Group matches not all settled"),
+            }
+        }
+
+        let standings = if void {
+            Vec::new()
+        } else {
+            group_table(&group.teams, &self.group_results(&group))
+        };
+        let no_pickers = self.group_pickers.get(name).map_or(0, |p| p.len()) == 0;
+        let group = self.groups.get_mut(name).expect("No such group");
+        group.standings = standings;
+        group.finalized = true;
+        group.void = void;
+        group.scored = void || no_pickers;
+        if void {
+            None
+        } else {
+            Some(group.standings.iter().map(|r| r.team.clone()).collect())
+        }
+    }
+
+    /// Credits up to `max_items` group pickers from a finalized group's cursor.
+    /// Returns the (user, points) credited and whether the group is fully scored.
+    pub fn score_group_batch(
+        &mut self,
+        name: &str,
+        max_items: u32,
+    ) -> (Vec<(ActorId, u32)>, bool) {
+        let group = self.groups.get(name).cloned().expect("No such group");
+        if !group.finalized {
+            panic!("Group not finalized");
+        }
+        if group.scored {
+            panic!("Group already scored");
+        }
+        let pickers = self.group_pickers.get(name).cloned().unwrap_or_default();
+
+        let start = group.scoring_cursor as usize;
+        let end = start.saturating_add(max_items as usize).min(pickers.len());
+
+        let mut awarded: Vec<(ActorId, u32)> = Vec::new();
+        for user in pickers[start..end].iter() {
+            let bonus = match self.group_picks.get(&(*user, name.to_string())) {
+                Some(pick) => group_bonus(&group, pick),
+                None => continue,
+            };
+            if bonus == 0 {
+                continue;
+            }
+            let pts = self.user_points.entry(*user).or_insert(0);
+            *pts = pts.saturating_add(bonus);
+            awarded.push((*user, bonus));
+        }
+
+        let group = self.groups.get_mut(name).expect("No such group");
+        group.scoring_cursor = end as u32;
+        group.scored = end >= pickers.len();
+        (awarded, group.scored)
+    }
+
+    /// Group bonus points `user` has earned on finalized groups the scoring crank has not
+    /// reached yet.
+    pub fn pending_group_points(&self, user: ActorId) -> u32 {
+        self.groups
+            .values()
+            .filter(|g| g.finalized && !g.scored)
+            .filter_map(|g| {
+                let pick = self.group_picks.get(&(user, g.name.clone()))?;
+                (pick.index >= g.scoring_cursor).then(|| group_bonus(g, pick))
+            })
+            .fold(0u32, |acc, p| acc.saturating_add(p))
+    }
+
+    /// Validates a survivor round without touching state: every match must exist, still
+    /// take bets and not belong to an earlier round. Returns the rejection reason, if any.
+    pub fn check_survivor_round(&self, match_ids: &[u64], now: u64) -> Result<(), &'static str> {
+        if self.survivor_finished {
+            return Err("Survivor game finished");
+        }
+        if match_ids.is_empty() {
+            return Err("Round needs matches");
+        }
+        for (i, match_id) in match_ids.iter().enumerate() {
+            if match_ids[..i].contains(match_id) {
+                return Err("Duplicate match in round");
+            }
+            let m = self.matches.get(match_id).ok_or("Match not found")?;
+            if now >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS) {
+                return Err("Round match betting closed");
+            }
+            if self.survivor_rounds.iter().any(|r| r.match_ids.contains(match_id)) {
+                return Err("Match already in a round");
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether betting has closed on any match of the first survivor round.
+    pub fn survivor_started(&self, now: u64) -> bool {
+        let first = match self.survivor_rounds.first() {
+            Some(round) => round,
+            None => return false,
+        };
+        first.match_ids.iter().filter_map(|id| self.matches.get(id)).any(|m| {
+            now >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS)
+        })
+    }
+
+    /// Whether `entry` survives `round`: it must have picked a team that won its match in
+    /// the round. A draw or a missing pick eliminates; a pick on an expired match survives.
+    pub fn survives_round(&self, entry: &SurvivorEntry, round: &SurvivorRound) -> bool {
+        let team = match entry.picks.iter().find(|(r, _)| *r == round.round_id) {
+            Some((_, team)) => team,
+            None => return false,
+        };
+        for match_id in round.match_ids.iter() {
+            let m = match self.matches.get(match_id) {
+                Some(m) => m,
+                None => continue,
+            };
+            let side = if m.home == *team {
+                1
+            } else if m.away == *team {
+                -1
+            } else {
+                continue;
+            };
+            return match m.result {
+                ResultStatus::Finalized { score, .. } => outcome(score) == side,
+                ResultStatus::Expired => true,
+                _ => false,
+            };
+        }
+        false
+    }
+
+    /// Ends the survivor game with `winners` sharing the pool. The remainder of the equal
+    /// split goes to the final prize, or to the protocol fees once it is finalized.
+    pub fn finish_survivor(&mut self, winners: u32) {
+        let remainder = self.survivor_pool.checked_rem(winners as u128).unwrap_or(0);
+        if remainder > 0 {
+            self.survivor_pool = self.survivor_pool.saturating_sub(remainder);
+            if self.final_prize_finalized {
+                self.protocol_fee_accumulated =
+                    self.protocol_fee_accumulated.saturating_add(remainder);
+            } else {
+                self.final_prize_accumulated =
+                    self.final_prize_accumulated.saturating_add(remainder);
+            }
+        }
+
+        self.survivor_finished = true;
+        self.survivor_winners = winners;
+        self.survivor_payout_pool = self.survivor_pool;
+    }
+
+    /// Settles up to `max_items` entrants of survivor round `round_id` from its cursor,
+    /// eliminating those who did not survive it. Returns the eliminated wallets and
+    /// whether the round is done.
+    pub fn settle_survivor_batch(
+        &mut self,
+        round_id: u32,
+        max_items: u32,
+    ) -> (Vec<ActorId>, bool) {
+        let round = self
+            .survivor_rounds
+            .get(round_id as usize)
+            .cloned()
+            .expect("No such round");
+        let start = round.cursor as usize;
+        let end = start
+            .saturating_add(max_items as usize)
+            .min(self.survivor_entrants.len());
+
+        let mut eliminated: Vec<ActorId> = Vec::new();
+        for i in start..end {
+            let entrant = self.survivor_entrants[i];
+            let survives = match self.survivor_entries.get(&entrant) {
+                Some(entry) if entry.alive => self.survives_round(entry, &round),
+                _ => continue,
+            };
+            if !survives {
+                if let Some(entry) = self.survivor_entries.get_mut(&entrant) {
+                    entry.alive = false;
+                    entry.eliminated_in = Some(round_id);
+                }
+                eliminated.push(entrant);
+            }
+        }
+
+        self.survivor_alive = self.survivor_alive.saturating_sub(eliminated.len() as u32);
+        let round = &mut self.survivor_rounds[round_id as usize];
+        round.cursor = end as u32;
+        round.eliminated = round.eliminated.saturating_add(eliminated.len() as u32);
+        round.settled = end >= self.survivor_entrants.len();
+        (eliminated, round.settled)
+    }
+
+    /// Validates a new challenge without touching state. Returns the rejection reason, if any.
+    pub fn check_challenge(
+        &self,
+        creator: ActorId,
+        match_id: u64,
+        opponent: Option<ActorId>,
+        prediction: Prediction,
+        stake: u128,
+        now: u64,
+    ) -> Result<(), &'static str> {
+        if opponent == Some(creator) {
+            return Err("Cannot challenge yourself");
+        }
+        if stake < MIN_BET_PLANCK {
+            return Err("Bet below minimum");
+        }
+        self.check_prediction(match_id, prediction.score, prediction.penalty_winner, now)
+    }
+
+    /// Validates accepting a challenge without touching state. Returns the rejection
+    /// reason, if any.
+    pub fn check_accept_challenge(
+        &self,
+        acceptor: ActorId,
+        challenge_id: u64,
+        prediction: Prediction,
+        now: u64,
+    ) -> Result<(), &'static str> {
+        let c = self.challenges.get(&challenge_id).ok_or("Challenge not found")?;
+        if c.status != ChallengeStatus::Open {
+            return Err("Challenge not open");
+        }
+        if c.creator == acceptor {
+            return Err("Cannot accept own challenge");
+        }
+        if c.opponent.is_some_and(|opponent| opponent != acceptor) {
+            return Err("Challenge reserved for another wallet");
+        }
+        if prediction == c.creator_prediction {
+            return Err("Counter prediction matches the challenge");
+        }
+        self.check_prediction(c.match_id, prediction.score, prediction.penalty_winner, now)
+    }
+
+    /// Validates a market bet without touching state. Returns the rejection reason, if any.
+    pub fn check_market_bet(
+        &self,
+        bettor: ActorId,
+        market_id: u64,
+        selection: MarketSelection,
+        amount: u128,
+        now: u64,
+    ) -> Result<(), &'static str> {
+        let market = self.markets.get(&market_id).ok_or("Market not found")?;
+        if market.status != MarketStatus::Open {
+            return Err("Market closed");
+        }
+        if !market_selections(market.kind).contains(&selection) {
+            return Err("Selection not offered by this market");
+        }
+        if amount < MIN_BET_PLANCK {
+            return Err("Bet below minimum");
+        }
+        if self.market_bets.contains_key(&(bettor, market_id)) {
+            return Err("Already bet");
+        }
+        let m = self.matches.get(&market.match_id).ok_or("Match not found")?;
+        if m.home.is_empty() || m.away.is_empty() {
+            return Err("Teams not known yet");
+        }
+        if now >= m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS) {
+            return Err("Betting closed");
+        }
+        Ok(())
+    }
+
+    /// Books a market bet already validated by `check_market_bet`: protocol fee and final
+    /// prize cut as for a match bet, the rest into the market pool.
+    /// Returns the stake that went into the pool.
+    pub fn record_market_bet(
+        &mut self,
+        bettor: ActorId,
+        market_id: u64,
+        selection: MarketSelection,
+        amount: u128,
+    ) -> u128 {
+        let (final_prize_cut, pool_cut) = self.take_protocol_fee(bettor, amount);
+        self.final_prize_accumulated =
+            self.final_prize_accumulated.saturating_add(final_prize_cut);
+
+        let market = self.markets.get_mut(&market_id).expect("Market not found");
+        market.pool = market.pool.saturating_add(pool_cut);
+        if let Some((_, stake)) = market.stakes.iter_mut().find(|(s, _)| *s == selection) {
+            *stake = stake.saturating_add(pool_cut);
+        }
+
+        self.market_bets.insert(
+            (bettor, market_id),
+            MarketBet {
+                user: bettor,
+                market_id,
+                selection,
+                stake_in_pool: pool_cut,
+                claimed: false,
+                paid_out: 0,
+            },
+        );
+
+        let stats = self.user_stats.entry(bettor).or_default();
+        stats.total_staked = stats.total_staked.saturating_add(amount);
+
+        pool_cut
+    }
+
+    /// Validates a bet without touching state. Returns the rejection reason, if any.
+    pub fn check_bet(
+        &self,
+        bettor: ActorId,
+        match_id: u64,
+        predicted_score: Score,
+        predicted_penalty_winner: Option<PenaltyWinner>,
+        amount: u128,
+        now: u64,
+    ) -> Result<(), &'static str> {
+        if !self.matches.contains_key(&match_id) {
+            return Err("Match not found");
+        }
+        if amount < MIN_BET_PLANCK {
+            return Err("Bet below minimum");
+        }
+        if self.bets.contains_key(&(bettor, match_id)) {
+            return Err("Already bet");
+        }
+        self.check_prediction(match_id, predicted_score, predicted_penalty_winner, now)
+    }
+
+    /// Checks the betting window and the score / penalty-winner rules shared by staked
+    /// bets and free predictions.
+    pub fn check_prediction(
+        &self,
+        match_id: u64,
+        predicted_score: Score,
+        predicted_penalty_winner: Option<PenaltyWinner>,
+        now: u64,
+    ) -> Result<(), &'static str> {
+        let m = self.matches.get(&match_id).ok_or("Match not found")?;
+        if m.home.is_empty() || m.away.is_empty() {
+            return Err("Teams not known yet");
+        }
+
+        let close_time = m.kick_off.saturating_sub(BET_CLOSE_WINDOW_SECONDS);
+        if now >= close_time {
+            return Err("Betting closed");
+        }
+        if predicted_score.home > 20 || predicted_score.away > 20 {
+            return Err("Score too high");
+        }
+
+        let phase_weight = self
+            .phases
+            .get(&m.phase)
+            .map(|p| p.points_weight)
+            .unwrap_or(1);
+        let predicted_draw = predicted_score.home == predicted_score.away;
+
+        if !is_knockout(phase_weight) {
+            if predicted_penalty_winner.is_some() {
+                return Err("Penalty winner not allowed in group stage");
+            }
+        } else if predicted_draw {
+            if predicted_penalty_winner.is_none() {
+                return Err("Knockout draw requires penalty winner");
+            }
+        } else if predicted_penalty_winner.is_some() {
+            return Err("Penalty winner only allowed when predicting draw");
+        }
+
+        Ok(())
+    }
+
+    /// Validates a dispute without touching state. Returns the rejection reason, if any.
+    pub fn check_dispute(
+        &self,
+        disputer: ActorId,
+        match_id: u64,
+        claimed_score: Score,
+        claimed_penalty_winner: Option<PenaltyWinner>,
+        now: u64,
+    ) -> Result<(), &'static str> {
+        if !self.bets.contains_key(&(disputer, match_id)) {
+            return Err("Only bettors on this match can dispute");
+        }
+        if claimed_score.home > 20 || claimed_score.away > 20 {
+            return Err("Score too high");
+        }
+
+        let m = self.matches.get(&match_id).ok_or("No such match")?;
+        let challenge_window_ms = self.challenge_window_ms(m);
+
+        match &m.result {
+            ResultStatus::Proposed { score, penalty_winner, proposed_at, .. } => {
+                if now >= proposed_at.saturating_add(challenge_window_ms) {
+                    return Err("Challenge window expired");
+                }
+                if *score == claimed_score && *penalty_winner == claimed_penalty_winner {
+                    return Err("Claimed result matches the proposal");
+                }
+            }
+            _ => return Err("No proposal to dispute"),
+        }
+
+        let already_open = self.disputes.get(&match_id).is_some_and(|list| {
+            list.iter()
+                .any(|d| d.disputer == disputer && d.status == DisputeStatus::Open)
+        });
+        if already_open {
+            return Err("Dispute already open");
+        }
+
+        Ok(())
+    }
+
+    /// Books a bet already validated by `check_bet`: splits `amount` into protocol fee,
+    /// referral cut, final prize and match pool, and updates every stake index.
+    /// Returns the stake that went into the match pool.
+    pub fn record_bet(
+        &mut self,
+        bettor: ActorId,
+        match_id: u64,
+        predicted_score: Score,
+        predicted_penalty_winner: Option<PenaltyWinner>,
+        amount: u128,
+        now: u64,
+    ) -> u128 {
+        let (final_prize_cut, rest) = self.take_protocol_fee(bettor, amount);
+        let jackpot_cut = amount.saturating_mul(self.jackpot_bps) / BPS_DENOMINATOR;
+        let match_pool_cut = rest.saturating_sub(jackpot_cut);
+
+        // The phase prize is funded from the final prize cut
+        let phase_prize_cut = amount.saturating_mul(self.phase_prize_bps) / BPS_DENOMINATOR;
+        let final_prize_cut = final_prize_cut.saturating_sub(phase_prize_cut);
+        if phase_prize_cut > 0 {
+            let phase = self.matches.get(&match_id).map(|m| m.phase.clone()).unwrap_or_default();
+            let pool = self.phase_prize_pools.entry(phase).or_insert(0);
+            *pool = pool.saturating_add(phase_prize_cut);
+        }
+
+        self.final_prize_accumulated =
+            self.final_prize_accumulated.saturating_add(final_prize_cut);
+
+        let m = self.matches.get_mut(&match_id).expect("Match not found");
+        m.match_prize_pool = m.match_prize_pool.saturating_add(match_pool_cut);
+        m.jackpot = m.jackpot.saturating_add(jackpot_cut);
+        m.has_bets = true;
+        if !m.participants.contains(&bettor) {
+            m.participants.push(bettor);
+        }
+        let phase_weight = self
+            .phases
+            .get(&m.phase)
+            .map(|p| p.points_weight)
+            .unwrap_or(1);
+
+        let bet = Bet {
+            user: bettor,
+            match_id,
+            score: predicted_score,
+            penalty_winner: predicted_penalty_winner,
+            stake_in_match_pool: match_pool_cut,
+            claimed: false,
+            points_credited: false,
+            paid_out: 0,
+            joker_multiplier: 1,
+            placed_at: now,
+        };
+        self.bets.insert((bettor, match_id), bet);
+
+        let side = payout_side(predicted_score, predicted_penalty_winner, phase_weight);
+        let side_total = self.side_stake.entry((match_id, side)).or_insert(0);
+        *side_total = side_total.saturating_add(match_pool_cut);
+        let exact_total = self
+            .exact_score_stake
+            .entry((match_id, predicted_score, predicted_penalty_winner))
+            .or_insert(0);
+        *exact_total = exact_total.saturating_add(match_pool_cut);
+
+        let phase = self.matches.get(&match_id).map(|m| m.phase.clone()).unwrap_or_default();
+        let first_in_phase = !self.user_bets.get(&bettor).is_some_and(|records| {
+            records
+                .iter()
+                .filter_map(|r| self.matches.get(&r.match_id))
+                .any(|m| m.phase == phase)
+        });
+        if first_in_phase {
+            self.phase_bettors.entry(phase).or_default().push(bettor);
+        }
+
+        let list = self.user_bets.entry(bettor).or_insert(Vec::new());
+        list.push(UserBetRecord {
+            match_id,
+            score: predicted_score,
+            penalty_winner: predicted_penalty_winner,
+            stake_in_match_pool: match_pool_cut,
+        });
+
+        let stats = self.user_stats.entry(bettor).or_default();
+        stats.total_staked = stats.total_staked.saturating_add(amount);
+        stats.bets = stats.bets.saturating_add(1);
+
+        match_pool_cut
+    }
+
+    /// Credits the points of `user`'s bet on a finalized match, once.
+    /// Returns the points added (0 if already credited or nothing earned).
+    pub fn credit_bet_points(&mut self, user: ActorId, match_id: u64) -> u32 {
+        let m = self.matches.get(&match_id).expect("No such match");
+        let (final_score, final_penalty_winner) = match m.result {
+            ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
+            _ => panic!("Match not finalized"),
+        };
+        let phase_weight = self
+            .phases
+            .get(&m.phase)
+            .map(|p| p.points_weight)
+            .unwrap_or(1);
+        let phase = m.phase.clone();
+
+        let bet = match self.bets.get_mut(&(user, match_id)) {
+            Some(b) => b,
+            None => return 0,
+        };
+        if bet.points_credited {
+            return 0;
+        }
+        bet.points_credited = true;
+
+        let outcome = prediction_outcome(
+            bet.score,
+            bet.penalty_winner,
+            final_score,
+            final_penalty_winner,
+            phase_weight,
+        );
+        let added_points = prediction_points(
+            bet.score,
+            bet.penalty_winner,
+            final_score,
+            final_penalty_winner,
+            phase_weight,
+        )
+        .saturating_mul(bet.joker_multiplier);
+        if added_points > 0 {
+            let pts = self.user_points.entry(user).or_insert(0);
+            *pts = pts.saturating_add(added_points);
+            let pts = self.phase_points.entry((user, phase.clone())).or_insert(0);
+            *pts = pts.saturating_add(added_points);
+        }
+        tally_outcome(self.user_stats.entry(user).or_default(), outcome, phase, added_points);
+        added_points
+    }
+
+    /// Profile of `user`: stored counters plus bets on finalized matches whose points are
+    /// still pending, with `best_streak` and `net_pnl` derived from the full history.
+    pub fn user_stats(&self, user: ActorId) -> UserStats {
+        let mut stats = self.user_stats.get(&user).cloned().unwrap_or_default();
+        let records = self.user_bets.get(&user).cloned().unwrap_or_default();
+
+        // (kick_off, scored) for every decided bet, to walk in match order
+        let mut decided: Vec<(u64, bool)> = Vec::new();
+        for r in records.iter() {
+            let bet = match self.bets.get(&(user, r.match_id)) {
+                Some(b) => b,
+                None => continue,
+            };
+            let m = match self.matches.get(&r.match_id) {
+                Some(m) => m,
+                None => continue,
+            };
+            let (final_score, final_penalty_winner) = match m.result {
+                ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
+                _ => continue,
+            };
+            let phase_weight = self
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+
+            let outcome = prediction_outcome(
+                bet.score,
+                bet.penalty_winner,
+                final_score,
+                final_penalty_winner,
+                phase_weight,
+            );
+            if !bet.points_credited {
+                let points = prediction_points(
+                    bet.score,
+                    bet.penalty_winner,
+                    final_score,
+                    final_penalty_winner,
+                    phase_weight,
+                )
+                .saturating_mul(bet.joker_multiplier);
+                tally_outcome(&mut stats, outcome, m.phase.clone(), points);
+            }
+            decided.push((m.kick_off, outcome != BetOutcome::Miss));
+        }
+
+        decided.sort_by_key(|(kick_off, _)| *kick_off);
+        let mut streak: u32 = 0;
+        for (_, scored) in decided {
+            streak = if scored { streak.saturating_add(1) } else { 0 };
+            stats.best_streak = stats.best_streak.max(streak);
+        }
+
+        let (streak, early_bird) = self.pending_phase_bonuses(user);
+        stats.streak_bonus = stats.streak_bonus.saturating_add(streak);
+        stats.early_bird_bonus = stats.early_bird_bonus.saturating_add(early_bird);
+
+        let returned = stats.total_won.saturating_add(stats.total_refunded);
+        stats.net_pnl = (returned as i128).saturating_sub(stats.total_staked as i128);
+        stats
+    }
+
+    /// Points `user` has earned on finalized matches but that are not yet in `user_points`.
+    pub fn pending_points(&self, user: ActorId) -> u32 {
+        let records = match self.user_bets.get(&user) {
+            Some(v) => v,
+            None => return 0,
+        };
+
+        let mut pending: u32 = 0;
+        for r in records.iter() {
+            let bet = match self.bets.get(&(user, r.match_id)) {
+                Some(b) if !b.points_credited => b,
+                _ => continue,
+            };
+            let m = match self.matches.get(&r.match_id) {
+                Some(m) => m,
+                None => continue,
+            };
+            let (final_score, final_penalty_winner) = match m.result {
+                ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
+                _ => continue,
+            };
+            let phase_weight = self
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+
+            let points = prediction_points(
+                bet.score,
+                bet.penalty_winner,
+                final_score,
+                final_penalty_winner,
+                phase_weight,
+            );
+            pending = pending.saturating_add(points.saturating_mul(bet.joker_multiplier));
+        }
+        pending
+    }
+
+    /// Leaderboard points of `user`: the materialized cache plus anything still pending.
+    pub fn total_points(&self, user: ActorId) -> u32 {
+        let (streak, early_bird) = self.pending_phase_bonuses(user);
+        self.user_points
+            .get(&user)
+            .cloned()
+            .unwrap_or(0)
+            .saturating_add(self.pending_points(user))
+            .saturating_add(self.pending_bracket_points(user))
+            .saturating_add(self.pending_group_points(user))
+            .saturating_add(streak)
+            .saturating_add(early_bird)
+    }
+
+    /// Materializes every pending match point of `user` into `user_points`.
+    /// Returns the (match_id, points) pairs credited.
+    pub fn sync_user_points(&mut self, user: ActorId) -> Vec<(u64, u32)> {
+        let finalized: Vec<u64> = match self.user_bets.get(&user) {
+            Some(records) => records
+                .iter()
+                .map(|r| r.match_id)
+                .filter(|id| {
+                    self.matches
+                        .get(id)
+                        .map(|m| matches!(m.result, ResultStatus::Finalized { .. }))
+                        .unwrap_or(false)
+                })
+                .collect(),
+            None => return Vec::new(),
+        };
+
+        let mut credited: Vec<(u64, u32)> = Vec::new();
+        for match_id in finalized {
+            let added_points = self.credit_bet_points(user, match_id);
+            if added_points > 0 {
+                credited.push((match_id, added_points));
+            }
+        }
+        credited
+    }
+
+    /// Credits the free-to-play points of `user`'s prediction on a finalized match, once.
+    pub fn credit_free_points(&mut self, user: ActorId, match_id: u64) -> u32 {
+        let m = self.matches.get(&match_id).expect("No such match");
+        let (final_score, final_penalty_winner) = match m.result {
+            ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
+            _ => panic!("Match not finalized"),
+        };
+        let phase_weight = self
+            .phases
+            .get(&m.phase)
+            .map(|p| p.points_weight)
+            .unwrap_or(1);
+
+        let prediction = match self.free_predictions.get_mut(&(user, match_id)) {
+            Some(p) => p,
+            None => return 0,
+        };
+        if prediction.points_credited {
+            return 0;
+        }
+        prediction.points_credited = true;
+
+        let added_points = prediction_points(
+            prediction.score,
+            prediction.penalty_winner,
+            final_score,
+            final_penalty_winner,
+            phase_weight,
+        );
+        if added_points > 0 {
+            let pts = self.free_points.entry(user).or_insert(0);
+            *pts = pts.saturating_add(added_points);
+        }
+        added_points
+    }
+
+    /// Free-to-play points of `user`: the materialized cache plus predictions on
+    /// finalized matches the settlement crank has not reached yet.
+    pub fn total_free_points(&self, user: ActorId) -> u32 {
+        let mut total = self.free_points.get(&user).cloned().unwrap_or(0);
+        let match_ids = match self.user_free_predictions.get(&user) {
+            Some(v) => v,
+            None => return total,
+        };
+        for match_id in match_ids.iter() {
+            let prediction = match self.free_predictions.get(&(user, *match_id)) {
+                Some(p) if !p.points_credited => p,
+                _ => continue,
+            };
+            let m = match self.matches.get(match_id) {
+                Some(m) => m,
+                None => continue,
+            };
+            let (final_score, final_penalty_winner) = match m.result {
+                ResultStatus::Finalized { score, penalty_winner } => (score, penalty_winner),
+                _ => continue,
+            };
+            let phase_weight = self
+                .phases
+                .get(&m.phase)
+                .map(|p| p.points_weight)
+                .unwrap_or(1);
+
+            total = total.saturating_add(prediction_points(
+                prediction.score,
+                prediction.penalty_winner,
+                final_score,
+                final_penalty_winner,
+                phase_weight,
+            ));
+        }
+        total
+    }
+
+    /// Credits points for up to `max_items` participants of a finalized match starting
+    /// at its settlement cursor, skipping bets whose owners already synced their points.
+    /// Returns the points awarded in this batch and whether every participant is done.
+    pub fn settle_batch(&mut self, match_id: u64, max_items: u32) -> (Vec<(ActorId, u32)>, bool) {
+        let batch: Vec<ActorId> = {
+            let m = self.matches.get(&match_id).expect("No such match");
+            if !matches!(m.result, ResultStatus::Finalized { .. }) {
+                panic!("Match not finalized");
+            }
+            if m.points_settled {
+                panic!("Settlement already complete");
+            }
+            let start = m.settlement_cursor as usize;
+            let end = start.saturating_add(max_items as usize).min(m.participants.len());
+            m.participants[start..end].to_vec()
+        };
+
+        let mut awarded: Vec<(ActorId, u32)> = Vec::new();
+        for participant in batch.iter() {
+            let added_points = self.credit_bet_points(*participant, match_id);
+            if added_points > 0 {
+                awarded.push((*participant, added_points));
+            }
+        }
+
+        let m = self.matches.get_mut(&match_id).expect("No such match");
+        m.settlement_cursor = m.settlement_cursor.saturating_add(batch.len() as u32);
+        m.points_settled = m.settlement_cursor as usize >= m.participants.len();
+
+        (awarded, m.points_settled)
+    }
+
+    /// Free-to-play counterpart of `settle_batch`: credits up to `max_items` free
+    /// predictors from the match's own free cursor, so paid settlement never waits on them.
+    pub fn settle_free_batch(
+        &mut self,
+        match_id: u64,
+        max_items: u32,
+    ) -> (Vec<(ActorId, u32)>, bool) {
+        let batch: Vec<ActorId> = {
+            let m = self.matches.get(&match_id).expect("No such match");
+            if !matches!(m.result, ResultStatus::Finalized { .. }) {
+                panic!("Match not finalized");
+            }
+            if m.free_points_settled {
+                panic!("Free settlement already complete");
+            }
+            let start = m.free_settlement_cursor as usize;
+            let end = start.saturating_add(max_items as usize).min(m.free_participants.len());
+            m.free_participants[start..end].to_vec()
+        };
+
+        let mut awarded: Vec<(ActorId, u32)> = Vec::new();
+        for predictor in batch.iter() {
+            let added_points = self.credit_free_points(*predictor, match_id);
+            if added_points > 0 {
+                awarded.push((*predictor, added_points));
+            }
+        }
+
+        let m = self.matches.get_mut(&match_id).expect("No such match");
+        m.free_settlement_cursor = m.free_settlement_cursor.saturating_add(batch.len() as u32);
+        m.free_points_settled = m.free_settlement_cursor as usize >= m.free_participants.len();
+
+        (awarded, m.free_points_settled)
+    }
+}
+
+/// Adds one decided prediction to a wallet's counters.
+fn tally_outcome(stats: &mut UserStats, outcome: BetOutcome, phase: String, points: u32) {
+    match outcome {
+        BetOutcome::ExactScore => stats.exact_hits = stats.exact_hits.saturating_add(1),
+        BetOutcome::CorrectOutcome => {
+            stats.correct_outcomes = stats.correct_outcomes.saturating_add(1)
+        }
+        _ => {}
+    }
+    if points == 0 {
+        return;
+    }
+    match stats.phase_points.iter_mut().find(|(name, _)| *name == phase) {
+        Some((_, total)) => *total = total.saturating_add(points),
+        None => stats.phase_points.push((phase, points)),
+    }
 }
 
 // ── Query projection ──────────────────────────────────────────────────────────
@@ -81,10 +1820,25 @@ pub struct IoSmartCupState {
     pub phases: Vec<PhaseConfig>,
     pub user_points: Vec<(ActorId, u32)>,
     pub podium_finalized: bool,
+    pub final_match_id: Option<u64>,
+    pub third_place_match_id: Option<u64>,
     pub r32_lock_time: Option<u64>,
     pub final_prize_finalized: bool,
     pub final_prize_claimable_total: u128,
     pub final_prize_rounding_dust: u128,
+    pub resolution_period_ms: u64,
+    pub dao_program: Option<ActorId>,
+    pub referral_fee_bps: u128,
+    pub payment_token: Option<ActorId>,
+    pub sponsor_pool: u128,
+    pub free_prize_finalized: bool,
+    pub prize_curve: PrizeCurve,
+    pub phase_prize_bps: u128,
+    pub phase_prize_pools: Vec<(String, u128)>,
+    pub jackpot_bps: u128,
+    pub parlay_pool: u128,
+    pub parlay_pool_finalized: bool,
+    pub exact_score_weight: u32,
 }
 
 impl From<SmartCupState> for IoSmartCupState {
@@ -101,10 +1855,29 @@ impl From<SmartCupState> for IoSmartCupState {
                 .map(|(id, pts)| (*id, *pts))
                 .collect(),
             podium_finalized: state.podium_finalized,
+            final_match_id: state.final_match_id,
+            third_place_match_id: state.third_place_match_id,
             r32_lock_time: state.r32_lock_time,
             final_prize_finalized: state.final_prize_finalized,
             final_prize_claimable_total: state.final_prize_claimable_total,
             final_prize_rounding_dust: state.final_prize_rounding_dust,
+            resolution_period_ms: state.resolution_period_ms,
+            dao_program: state.dao_program,
+            referral_fee_bps: state.referral_fee_bps,
+            payment_token: state.payment_token,
+            sponsor_pool: state.sponsor_pool,
+            free_prize_finalized: state.free_prize_finalized,
+            prize_curve: state.prize_curve,
+            phase_prize_bps: state.phase_prize_bps,
+            phase_prize_pools: state
+                .phase_prize_pools
+                .iter()
+                .map(|(phase, pool)| (phase.clone(), *pool))
+                .collect(),
+            jackpot_bps: state.jackpot_bps,
+            parlay_pool: state.parlay_pool,
+            parlay_pool_finalized: state.parlay_pool_finalized,
+            exact_score_weight: state.exact_score_weight,
         }
     }
 }
@@ -1,4 +1,5 @@
 use sails_rs::prelude::*;
+use super::constants::FINAL_PRIZE_TOP5_BPS;
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
 #[codec(crate = sails_rs::scale_codec)]
@@ -12,7 +13,7 @@ pub struct FinalPrizeClaimStatus {
     pub points: u32,
 }
 
-#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq, Hash)]
 #[codec(crate = sails_rs::scale_codec)]
 #[scale_info(crate = sails_rs::scale_info)]
 pub struct Score {
@@ -20,7 +21,7 @@ pub struct Score {
     pub away: u8,
 }
 
-#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq, Hash)]
 #[codec(crate = sails_rs::scale_codec)]
 #[scale_info(crate = sails_rs::scale_info)]
 pub enum PenaltyWinner {
@@ -43,6 +44,144 @@ pub enum ResultStatus {
         score: Score,
         penalty_winner: Option<PenaltyWinner>,
     },
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum DisputeStatus {
+    Open,
+    Upheld,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct Dispute {
+    pub disputer: ActorId,
+    pub claimed_score: Score,
+    pub claimed_penalty_winner: Option<PenaltyWinner>,
+    pub bond: u128,
+    pub opened_at: u64,
+    pub status: DisputeStatus,
+}
+
+/// How a prize pool is split over a points-sorted leaderboard.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum PrizeCurve {
+    /// BPS share per position (1st, 2nd, ...), summing to `BPS_DENOMINATOR`.
+    Table(Vec<u128>),
+    /// Pool split pro rata to points among the top N wallets.
+    ProportionalTopN(u32),
+    /// Pool split pro rata to points among the top share of wallets, in BPS (100 = top 1%).
+    ProportionalTopBps(u128),
+}
+
+impl Default for PrizeCurve {
+    fn default() -> Self {
+        PrizeCurve::Table(FINAL_PRIZE_TOP5_BPS.to_vec())
+    }
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct JackpotStatus {
+    /// Jackpot of the next match awaiting its result, by kick-off order.
+    pub next_match_id: Option<u64>,
+    pub next_jackpot: u128,
+    /// Jackpots still held by matches awaiting their result.
+    pub total_pending: u128,
+    pub jackpot_bps: u128,
+}
+
+/// Program balance against everything it still owes. Exactly one of `surplus` and
+/// `deficit` is non-zero unless the two sides match.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct SolvencyReport {
+    /// VFT program holding the funds in token mode. The report cannot read that balance,
+    /// so `balance`, `surplus` and `deficit` stay zero; compare `total_liabilities` with
+    /// the program's `BalanceOf` on the token instead.
+    pub payment_token: Option<ActorId>,
+    /// Native balance (`exec::value_available`); zero in token mode.
+    pub balance: u128,
+    /// Pool left to claim on settled, not yet swept matches.
+    pub unclaimed_match_rewards: u128,
+    /// Pools of matches not settled yet, plus unrefunded stakes on expired matches.
+    pub unswept_match_pools: u128,
+    pub final_prize_accumulated: u128,
+    /// Allocated final prize not claimed yet.
+    pub unclaimed_final_prize: u128,
+    pub protocol_fee_accumulated: u128,
+    pub final_prize_rounding_dust: u128,
+    /// Referral rewards, sponsor, phase prize, parlay, market and survivor pools, challenge
+    /// escrow, parked payouts and open dispute bonds.
+    pub other_liabilities: u128,
+    pub total_liabilities: u128,
+    pub surplus: u128,
+    pub deficit: u128,
+}
+
+/// How a prediction fared against its match.
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum BetOutcome {
+    Pending,
+    ExactScore,
+    CorrectOutcome,
+    Miss,
+    Expired,
+}
+
+/// Per-wallet profile counters. `best_streak` and `net_pnl` are derived when queried.
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct UserStats {
+    /// Gross amount sent with bets, fees included.
+    pub total_staked: u128,
+    /// Match rewards claimed.
+    pub total_won: u128,
+    /// Match-pool stakes refunded on expired matches.
+    pub total_refunded: u128,
+    /// `total_won + total_refunded - total_staked`.
+    pub net_pnl: i128,
+    pub bets: u32,
+    pub exact_hits: u32,
+    /// Correct outcome without the exact score.
+    pub correct_outcomes: u32,
+    /// Longest run of scoring predictions in kick-off order.
+    pub best_streak: u32,
+    pub phase_points: Vec<(String, u32)>,
+    /// Streak and early-bird bonus points, including those of completed phases not yet
+    /// settled.
+    pub streak_bonus: u32,
+    pub early_bird_bonus: u32,
+}
+
+/// One bet in a wallet's history, with its outcome and payout.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct BetHistoryEntry {
+    pub match_id: u64,
+    pub phase: String,
+    pub kick_off: u64,
+    pub score: Score,
+    pub penalty_winner: Option<PenaltyWinner>,
+    pub stake_in_match_pool: u128,
+    pub outcome: BetOutcome,
+    pub points: u32,
+    /// Match reward share (or refund on expiry) the bet is entitled to.
+    pub payout: u128,
+    pub claimed: bool,
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
@@ -62,6 +201,8 @@ pub struct PhaseConfig {
     pub start_time: u64,
     pub end_time: u64,
     pub points_weight: u32,
+    pub challenge_window_ms: u64,
+    pub claim_deadline_ms: u64,
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
@@ -73,15 +214,39 @@ pub struct Match {
     pub home: String,
     pub away: String,
     pub kick_off: u64,
+    pub resolution_deadline: u64,
     pub result: ResultStatus,
+    pub open_disputes: u32,
     pub match_prize_pool: u128,
     pub has_bets: bool,
     pub participants: Vec<ActorId>,
+    /// Free-to-play predictors; credited by `continue_free_settlement`.
+    pub free_participants: Vec<ActorId>,
+    /// Winning match-pool stake with exact-score hits counted `exact_score_weight` times.
     pub total_winner_stake: u128,
+    /// Weighted winner stake already claimed; the dust sweep waits for all of it.
+    pub claimed_winner_stake: u128,
     pub total_claimed: u128,
     pub settlement_prepared: bool,
+    pub settlement_cursor: u32,
+    pub points_settled: bool,
+    /// Free predictors credited so far; independent of `settlement_cursor`.
+    pub free_settlement_cursor: u32,
+    /// Every free predictor credited; only the free-to-play prize waits on it.
+    pub free_points_settled: bool,
     pub dust_swept: bool,
     pub finalized_at: Option<u64>,
+    /// Block timestamp of expiry; unclaimed refunds are swept after the claim deadline.
+    pub expired_at: Option<u64>,
+    /// Exact-score jackpot: this match's cut plus anything rolled over into it.
+    pub jackpot: u128,
+    /// Match-pool stake that predicted the exact final result; 0 = jackpot rolled over.
+    pub jackpot_winner_stake: u128,
+    pub jackpot_claimed: u128,
+    /// Placeholder knockout match: home is the winner of the first match, away of the
+    /// second. Team names stay empty until their feeder finalizes; betting opens once
+    /// both are known.
+    pub feeders: Option<(u64, u64)>,
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo)]
@@ -94,6 +259,287 @@ pub struct Bet {
     pub penalty_winner: Option<PenaltyWinner>,
     pub stake_in_match_pool: u128,
     pub claimed: bool,
+    pub points_credited: bool,
+    /// Amount paid out on claim (reward share or expiry refund).
+    pub paid_out: u128,
+    /// Points multiplier: 1, or the joker multiplier once a joker is applied.
+    pub joker_multiplier: u32,
+    /// Block timestamp the bet was placed at, for the early-bird bonus.
+    pub placed_at: u64,
+}
+
+/// Zero-stake prediction; earns points on the free-to-play leaderboard only.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct FreePrediction {
+    pub user: ActorId,
+    pub match_id: u64,
+    pub score: Score,
+    pub penalty_winner: Option<PenaltyWinner>,
+    pub points_credited: bool,
+}
+
+/// Knockout bracket slot: one tie whose winner advances. First-round slots have no
+/// feeders; later slots are fed by the winners of two earlier slots.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct BracketSlot {
+    pub slot_id: u32,
+    pub match_id: Option<u64>,
+    pub feeders: Option<(u32, u32)>,
+    /// Advancing team, set when the slot's match finalizes.
+    pub winner: Option<String>,
+    /// Points for a correct pick: the phase weight of the slot's match.
+    pub points: u32,
+    /// Scoring cursor into the bracket entrant list.
+    pub scoring_cursor: u32,
+    pub scored: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct BracketEntry {
+    pub user: ActorId,
+    /// Advancing team picked per slot, indexed by slot id.
+    pub picks: Vec<String>,
+    /// Position in the bracket entrant list.
+    pub index: u32,
+    /// Bracket points credited so far.
+    pub points: u32,
+}
+
+/// A team's row in a group table.
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct GroupStanding {
+    pub team: String,
+    pub played: u32,
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+    pub goals_for: u32,
+    pub goals_against: u32,
+    pub points: u32,
+}
+
+/// Group-stage group: a set of matches whose teams are ranked in a table.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct Group {
+    pub name: String,
+    pub match_ids: Vec<u64>,
+    /// Teams in order of first appearance; the last tie-breaker of the table.
+    pub teams: Vec<String>,
+    /// Final table, set when the group finalizes.
+    pub standings: Vec<GroupStanding>,
+    pub finalized: bool,
+    /// A group match expired: no placement bonus is awarded.
+    pub void: bool,
+    /// Scoring cursor into the group's picker list.
+    pub scoring_cursor: u32,
+    pub scored: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct GroupPick {
+    pub first: String,
+    pub second: String,
+    /// Position in the group's picker list.
+    pub index: u32,
+}
+
+/// Survivor round: a set of matches in which each living entrant picks one team to win.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct SurvivorRound {
+    pub round_id: u32,
+    pub match_ids: Vec<u64>,
+    /// Settlement cursor into the survivor entrant list.
+    pub cursor: u32,
+    pub eliminated: u32,
+    pub settled: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct SurvivorEntry {
+    pub entrant: ActorId,
+    pub alive: bool,
+    pub eliminated_in: Option<u32>,
+    /// (round_id, team) per pick made.
+    pub picks: Vec<(u32, String)>,
+    pub claimed: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct SurvivorStatus {
+    pub entry_fee: u128,
+    pub pool: u128,
+    pub entrants: u32,
+    pub alive: u32,
+    pub rounds: Vec<SurvivorRound>,
+    pub finished: bool,
+    pub winners: u32,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct Prediction {
+    pub score: Score,
+    pub penalty_winner: Option<PenaltyWinner>,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum ChallengeStatus {
+    Open,
+    Accepted,
+    Settled,
+    /// Cancelled by the creator or not accepted before betting closed.
+    Refunded,
+}
+
+/// Head-to-head bet between two wallets with escrowed, equal stakes.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct Challenge {
+    pub challenge_id: u64,
+    pub match_id: u64,
+    pub creator: ActorId,
+    /// Only this wallet may accept; `None` = open to anyone.
+    pub opponent: Option<ActorId>,
+    pub creator_prediction: Prediction,
+    pub acceptor: Option<ActorId>,
+    pub acceptor_prediction: Option<Prediction>,
+    /// Stake of each side.
+    pub stake: u128,
+    pub status: ChallengeStatus,
+    /// `None` once settled means the predictions drew and both sides were refunded.
+    pub winner: Option<ActorId>,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum MarketKind {
+    /// Total goals against the line.
+    OverUnder,
+    BothTeamsToScore,
+    /// Home goals plus the line against away goals.
+    AsianHandicap,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct MarketParams {
+    /// Goal line in hundredths of a goal (250 = 2.5); whole or half goals only, 0 for
+    /// both-teams-to-score.
+    pub line: i32,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum MarketSelection {
+    Over,
+    Under,
+    Yes,
+    No,
+    Home,
+    Away,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum MarketStatus {
+    Open,
+    Settled,
+    /// Push on a whole line, or the match expired: every stake is refundable.
+    Void,
+}
+
+/// Pari-mutuel side market on a match, settled from its finalized score.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct Market {
+    pub market_id: u64,
+    pub match_id: u64,
+    pub kind: MarketKind,
+    pub params: MarketParams,
+    pub pool: u128,
+    /// Pool stake per selection.
+    pub stakes: Vec<(MarketSelection, u128)>,
+    pub status: MarketStatus,
+    pub winning_selection: Option<MarketSelection>,
+    pub winner_stake: u128,
+    pub claimed_winner_stake: u128,
+    pub total_claimed: u128,
+    pub dust_swept: bool,
+}
+
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct MarketBet {
+    pub user: ActorId,
+    pub market_id: u64,
+    pub selection: MarketSelection,
+    pub stake_in_pool: u128,
+    pub claimed: bool,
+    pub paid_out: u128,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct ParlayLeg {
+    pub match_id: u64,
+    pub score: Score,
+    pub penalty_winner: Option<PenaltyWinner>,
+}
+
+#[derive(Debug, Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum ParlayStatus {
+    Open,
+    Won,
+    Lost,
+    /// Every leg was voided (expired match); the pool stake is refundable.
+    Void,
+}
+
+/// Accumulator over several matches, paid from the shared parlay pool.
+#[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct Parlay {
+    pub parlay_id: u64,
+    pub owner: ActorId,
+    pub legs: Vec<ParlayLeg>,
+    pub stake_in_pool: u128,
+    pub status: ParlayStatus,
+    /// Legs left once voided ones are dropped; set on settlement and used as the weight.
+    pub live_legs: u32,
+    pub claimed: bool,
+    pub paid_out: u128,
 }
 
 #[derive(Debug, Clone, Encode, Decode, TypeInfo, PartialEq, Eq)]
@@ -134,3 +580,14 @@ pub struct PodiumResult {
     pub runner_up: String,
     pub third_place: String,
 }
+
+#[derive(Debug, Clone, Default, Encode, Decode, TypeInfo, PartialEq, Eq)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct ReferralStats {
+    pub code: Option<String>,
+    pub referred_users: u32,
+    pub volume: u128,
+    pub earned: u128,
+    pub claimable: u128,
+}
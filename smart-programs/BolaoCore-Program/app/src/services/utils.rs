@@ -1,6 +1,9 @@
 use sails_rs::prelude::*;
-use super::constants::FINAL_PRIZE_TOP5_BPS;
-use super::types::{Score, PenaltyWinner};
+use super::constants::{BPS_DENOMINATOR, GROUP_FIRST_PLACE_POINTS, GROUP_SECOND_PLACE_POINTS};
+use super::types::{
+    Score, PenaltyWinner, BetOutcome, PrizeCurve, MarketKind, MarketParams, MarketSelection,
+    GroupStanding, Group, GroupPick,
+};
 use super::state::SmartCupState;
 
 /// Returns 1 = home win, 0 = draw, -1 = away win.
@@ -33,6 +36,19 @@ pub fn is_knockout(points_weight: u32) -> bool {
     points_weight > 1
 }
 
+/// Side a score backs for settlement: the outcome in group stage, the advancing side in
+/// knockouts. A knockout draw without a penalty winner backs no side (0).
+/// A bet is eligible for payout exactly when its side equals the final result's side.
+pub fn payout_side(score: Score, penalty_winner: Option<PenaltyWinner>, phase_weight: u32) -> i8 {
+    if !is_knockout(phase_weight) {
+        return outcome(score);
+    }
+    if score.home == score.away && penalty_winner.is_none() {
+        return 0;
+    }
+    advance_outcome(score, penalty_winner)
+}
+
 /// Returns true if a bet is eligible for a payout given the finalized match result.
 pub fn eligible_for_payout(
     bet_score: Score,
@@ -69,16 +85,214 @@ pub fn eligible_for_payout(
     bet_adv == final_adv
 }
 
-/// Sums BPS shares for positions start_pos..=end_pos_inclusive (1-indexed).
-pub fn top5_share_sum_bps(start_pos: usize, end_pos_inclusive: usize) -> u128 {
+/// Points earned by a prediction given the finalized match result:
+/// exact score (and penalty winner on knockout draws) = 3× weight, correct outcome = 1× weight.
+pub fn prediction_points(
+    bet_score: Score,
+    bet_penalty_winner: Option<PenaltyWinner>,
+    final_score: Score,
+    final_penalty_winner: Option<PenaltyWinner>,
+    phase_weight: u32,
+) -> u32 {
+    match prediction_outcome(
+        bet_score,
+        bet_penalty_winner,
+        final_score,
+        final_penalty_winner,
+        phase_weight,
+    ) {
+        BetOutcome::ExactScore => 3u32.saturating_mul(phase_weight),
+        BetOutcome::CorrectOutcome => phase_weight,
+        _ => 0,
+    }
+}
+
+/// Classifies a prediction against the finalized result: `ExactScore`, `CorrectOutcome`
+/// (advancing side in knockouts) or `Miss`.
+pub fn prediction_outcome(
+    bet_score: Score,
+    bet_penalty_winner: Option<PenaltyWinner>,
+    final_score: Score,
+    final_penalty_winner: Option<PenaltyWinner>,
+    phase_weight: u32,
+) -> BetOutcome {
+    let knockout = is_knockout(phase_weight);
+    let draw_final = final_score.home == final_score.away;
+
+    let final_outcome = if knockout {
+        advance_outcome(final_score, final_penalty_winner)
+    } else {
+        outcome(final_score)
+    };
+
+    let bet_outcome = if knockout {
+        if bet_score.home == bet_score.away {
+            if bet_penalty_winner.is_none() {
+                0
+            } else {
+                advance_outcome(bet_score, bet_penalty_winner)
+            }
+        } else {
+            outcome(bet_score)
+        }
+    } else {
+        outcome(bet_score)
+    };
+
+    let penalties_correct = if knockout && draw_final {
+        bet_penalty_winner.is_some() && bet_penalty_winner == final_penalty_winner
+    } else {
+        true
+    };
+
+    if bet_score == final_score && penalties_correct {
+        BetOutcome::ExactScore
+    } else if bet_outcome == final_outcome {
+        BetOutcome::CorrectOutcome
+    } else {
+        BetOutcome::Miss
+    }
+}
+
+/// The two selections of a market kind.
+pub fn market_selections(kind: MarketKind) -> [MarketSelection; 2] {
+    match kind {
+        MarketKind::OverUnder => [MarketSelection::Over, MarketSelection::Under],
+        MarketKind::BothTeamsToScore => [MarketSelection::Yes, MarketSelection::No],
+        MarketKind::AsianHandicap => [MarketSelection::Home, MarketSelection::Away],
+    }
+}
+
+/// Winning selection of a market for the final score, or `None` on a push.
+pub fn market_winner(
+    kind: MarketKind,
+    params: MarketParams,
+    score: Score,
+) -> Option<MarketSelection> {
+    let home = score.home as i32 * 100;
+    let away = score.away as i32 * 100;
+    match kind {
+        MarketKind::OverUnder => match (home + away).cmp(&params.line) {
+            core::cmp::Ordering::Greater => Some(MarketSelection::Over),
+            core::cmp::Ordering::Less => Some(MarketSelection::Under),
+            core::cmp::Ordering::Equal => None,
+        },
+        MarketKind::BothTeamsToScore => {
+            if score.home > 0 && score.away > 0 {
+                Some(MarketSelection::Yes)
+            } else {
+                Some(MarketSelection::No)
+            }
+        }
+        MarketKind::AsianHandicap => match (home + params.line).cmp(&away) {
+            core::cmp::Ordering::Greater => Some(MarketSelection::Home),
+            core::cmp::Ordering::Less => Some(MarketSelection::Away),
+            core::cmp::Ordering::Equal => None,
+        },
+    }
+}
+
+/// Group table from `(home, away, score)` results: 3 points for a win, 1 for a draw,
+/// ranked by points, goal difference and goals scored. Remaining ties keep `teams` order.
+pub fn group_table(teams: &[String], results: &[(String, String, Score)]) -> Vec<GroupStanding> {
+    let mut table: Vec<GroupStanding> = teams
+        .iter()
+        .map(|team| GroupStanding { team: team.clone(), ..Default::default() })
+        .collect();
+
+    for (home, away, score) in results.iter() {
+        let sides = [(home, score.home, score.away), (away, score.away, score.home)];
+        for (team, scored, conceded) in sides {
+            let row = match table.iter_mut().find(|r| r.team == *team) {
+                Some(r) => r,
+                None => continue,
+            };
+            row.played = row.played.saturating_add(1);
+            row.goals_for = row.goals_for.saturating_add(scored as u32);
+            row.goals_against = row.goals_against.saturating_add(conceded as u32);
+            if scored > conceded {
+                row.won = row.won.saturating_add(1);
+                row.points = row.points.saturating_add(3);
+            } else if scored == conceded {
+                row.drawn = row.drawn.saturating_add(1);
+                row.points = row.points.saturating_add(1);
+            } else {
+                row.lost = row.lost.saturating_add(1);
+            }
+        }
+    }
+
+    // Stable sort: teams level on every criterion keep their registration order
+    table.sort_by(|a, b| {
+        let diff = |r: &GroupStanding| r.goals_for as i64 - r.goals_against as i64;
+        b.points
+            .cmp(&a.points)
+            .then(diff(b).cmp(&diff(a)))
+            .then(b.goals_for.cmp(&a.goals_for))
+    });
+    table
+}
+
+/// Placement bonus a pick earns on a finalized group.
+pub fn group_bonus(group: &Group, pick: &GroupPick) -> u32 {
+    let mut bonus: u32 = 0;
+    if group.standings.first().is_some_and(|r| r.team == pick.first) {
+        bonus = bonus.saturating_add(GROUP_FIRST_PLACE_POINTS);
+    }
+    if group.standings.get(1).is_some_and(|r| r.team == pick.second) {
+        bonus = bonus.saturating_add(GROUP_SECOND_PLACE_POINTS);
+    }
+    bonus
+}
+
+/// Streak bonus over scoring flags in kick-off order: every maximal run of scoring
+/// predictions earns the points of the longest threshold it reaches. `thresholds` are
+/// `(run length, points)` in increasing run length.
+pub fn streak_bonus(scored: &[bool], thresholds: &[(u32, u32)]) -> u32 {
+    let mut bonus: u32 = 0;
+    let mut run: u32 = 0;
+    for hit in scored.iter().copied().chain(core::iter::once(false)) {
+        if hit {
+            run = run.saturating_add(1);
+            continue;
+        }
+        if let Some((_, points)) = thresholds.iter().rev().find(|(len, _)| run >= *len) {
+            bonus = bonus.saturating_add(*points);
+        }
+        run = 0;
+    }
+    bonus
+}
+
+/// Share of a match jackpot owed to an exact-score bet of `stake`.
+pub fn jackpot_share(stake: u128, jackpot: u128, jackpot_winner_stake: u128) -> u128 {
+    stake
+        .saturating_mul(jackpot)
+        .checked_div(jackpot_winner_stake)
+        .unwrap_or(0)
+}
+
+/// A winning bet's stake as counted in the match reward split: exact-score hits count
+/// `exact_score_weight` times.
+pub fn weighted_winner_stake(stake: u128, exact_hit: bool, exact_score_weight: u32) -> u128 {
+    if exact_hit {
+        stake.saturating_mul(exact_score_weight as u128)
+    } else {
+        stake
+    }
+}
+
+/// Sums the BPS shares of `table` for positions start_pos..=end_pos_inclusive (1-indexed).
+pub fn share_sum_bps(table: &[u128], start_pos: usize, end_pos_inclusive: usize) -> u128 {
     let mut total = 0u128;
     for pos in start_pos..=end_pos_inclusive {
-        total = total.saturating_add(FINAL_PRIZE_TOP5_BPS[pos - 1]);
+        total = total.saturating_add(table[pos - 1]);
     }
     total
 }
 
-/// Returns all participants sorted by points descending.
+/// Returns all participants sorted by points descending, read from the materialized
+/// `user_points` cache — every finalized match must be `points_settled` first.
 /// Only includes wallets that placed at least one bet with stake > 0.
 pub fn collect_leaderboard(state: &SmartCupState) -> Vec<(ActorId, u32)> {
     let mut leaderboard: Vec<(ActorId, u32)> = state
@@ -94,3 +308,154 @@ pub fn collect_leaderboard(state: &SmartCupState) -> Vec<(ActorId, u32)> {
     leaderboard.sort_by(|a, b| b.1.cmp(&a.1));
     leaderboard
 }
+
+/// Leaderboard of one phase: every staked participant of the phase's matches with the
+/// points they earned in it, sorted descending. Reads the materialized `phase_points`,
+/// so the phase's matches must be `points_settled` first.
+pub fn collect_phase_leaderboard(state: &SmartCupState, phase: &str) -> Vec<(ActorId, u32)> {
+    let mut wallets: Vec<ActorId> = state
+        .matches
+        .values()
+        .filter(|m| m.phase == phase)
+        .flat_map(|m| m.participants.iter().cloned())
+        .collect();
+    wallets.sort_unstable();
+    wallets.dedup();
+
+    let mut leaderboard: Vec<(ActorId, u32)> = wallets
+        .into_iter()
+        .map(|wallet| {
+            let points = state
+                .phase_points
+                .get(&(wallet, String::from(phase)))
+                .cloned()
+                .unwrap_or(0);
+            (wallet, points)
+        })
+        .collect();
+
+    leaderboard.sort_by(|a, b| b.1.cmp(&a.1));
+    leaderboard
+}
+
+/// Free-to-play leaderboard sorted by points descending, read from the materialized
+/// `free_points` cache. Includes every wallet with at least one free prediction.
+pub fn collect_free_leaderboard(state: &SmartCupState) -> Vec<(ActorId, u32)> {
+    let mut leaderboard: Vec<(ActorId, u32)> = state
+        .user_free_predictions
+        .keys()
+        .map(|wallet| (*wallet, state.free_points.get(wallet).cloned().unwrap_or(0)))
+        .collect();
+
+    leaderboard.sort_by(|a, b| b.1.cmp(&a.1));
+    leaderboard
+}
+
+/// Splits `pool` over a points-sorted leaderboard according to `curve`.
+/// Returns each rewarded wallet with its amount and the total allocated; the rest of
+/// `pool` is rounding dust.
+pub fn allocate_by_rank(
+    leaderboard: &[(ActorId, u32)],
+    pool: u128,
+    curve: &PrizeCurve,
+) -> (Vec<(ActorId, u128)>, u128) {
+    match curve {
+        PrizeCurve::Table(table) => allocate_by_table(leaderboard, pool, table),
+        PrizeCurve::ProportionalTopN(top_n) => {
+            allocate_proportional(leaderboard, pool, *top_n as usize)
+        }
+        PrizeCurve::ProportionalTopBps(top_bps) => {
+            let top_n = (leaderboard.len() as u128)
+                .saturating_mul(*top_bps)
+                .div_ceil(BPS_DENOMINATOR)
+                .max(1);
+            allocate_proportional(leaderboard, pool, top_n as usize)
+        }
+    }
+}
+
+/// Payout-table split. Tied wallets share the summed BPS of every position their group
+/// spans (capped at the table length).
+fn allocate_by_table(
+    leaderboard: &[(ActorId, u32)],
+    pool: u128,
+    table: &[u128],
+) -> (Vec<(ActorId, u128)>, u128) {
+    let positions = table.len();
+    let mut allocations: Vec<(ActorId, u128)> = Vec::new();
+    let mut i: usize = 0;
+    let mut current_position: usize = 1;
+    let mut total_allocated: u128 = 0;
+
+    while i < leaderboard.len() && current_position <= positions {
+        let tied_points = leaderboard[i].1;
+        let mut j = i + 1;
+
+        while j < leaderboard.len() && leaderboard[j].1 == tied_points {
+            j += 1;
+        }
+
+        let group_size = j - i; // always >= 1 by loop invariant
+        let start_pos = current_position;
+        let end_pos = current_position + group_size - 1;
+        let affected_end = end_pos.min(positions);
+
+        let group_bps = share_sum_bps(table, start_pos, affected_end);
+        if group_bps > 0 {
+            let group_amount = pool.saturating_mul(group_bps) / BPS_DENOMINATOR;
+
+            let per_wallet = group_amount
+                .checked_div(group_size as u128)
+                .expect("Division by zero: group_size is zero");
+
+            if per_wallet > 0 {
+                for entry in &leaderboard[i..j] {
+                    allocations.push((entry.0, per_wallet));
+                }
+                total_allocated = total_allocated
+                    .saturating_add(per_wallet.saturating_mul(group_size as u128));
+            }
+        }
+
+        current_position = current_position.saturating_add(group_size);
+        i = j;
+    }
+
+    (allocations, total_allocated)
+}
+
+/// Pro-rata split among the top `top_n` wallets; wallets tied with the last qualifying
+/// one are included too. Splits equally if nobody in the cut has points.
+fn allocate_proportional(
+    leaderboard: &[(ActorId, u32)],
+    pool: u128,
+    top_n: usize,
+) -> (Vec<(ActorId, u128)>, u128) {
+    if leaderboard.is_empty() || top_n == 0 {
+        return (Vec::new(), 0);
+    }
+
+    let mut cut = top_n.min(leaderboard.len());
+    let cutoff_points = leaderboard[cut - 1].1;
+    while cut < leaderboard.len() && leaderboard[cut].1 == cutoff_points {
+        cut += 1;
+    }
+    let winners = &leaderboard[..cut];
+
+    let total_points: u128 = winners.iter().map(|(_, pts)| *pts as u128).sum();
+    let mut allocations: Vec<(ActorId, u128)> = Vec::new();
+    let mut total_allocated: u128 = 0;
+    for (wallet, pts) in winners.iter() {
+        let amount = if total_points == 0 {
+            pool / cut as u128
+        } else {
+            pool.saturating_mul(*pts as u128) / total_points
+        };
+        if amount > 0 {
+            allocations.push((*wallet, amount));
+            total_allocated = total_allocated.saturating_add(amount);
+        }
+    }
+
+    (allocations, total_allocated)
+}
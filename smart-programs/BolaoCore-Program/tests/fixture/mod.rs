@@ -1,6 +1,6 @@
 use sails_rs::{
     client::{Actor, GearEnv, GtestEnv},
-    gtest::System,
+    gtest::{Program, System, WasmProgram},
     prelude::*,
 };
 use bolao_program::{
@@ -22,9 +22,6 @@ pub fn actor(id: u64) -> ActorId {
 pub struct Fixture {
     pub env: GtestEnv,
     pub program: Actor<BolaoProgram, GtestEnv>,
-    /// Shared handle to the gtest simulation — used to advance block time.
-    /// System is Rc-based internally, so this clone shares state with the env.
-    pub system: System,
 }
 
 impl Fixture {
@@ -33,11 +30,9 @@ impl Fixture {
         system.init_logger();
 
         for id in [ADMIN, NEW_ADMIN, STRANGER, ORACLE, USER1, USER2] {
-            system.mint_to(id, 100_000_000_000_000);
+            system.mint_to(id, 100_000_000_000_000_000);
         }
 
-        // Clone before moving into GtestEnv — both handles share the same Rc<RefCell<...>>.
-        let system_ref = system.clone();
         let code_id = system.submit_code(WASM_BINARY);
         let env = GtestEnv::new(system, actor(ADMIN));
 
@@ -47,7 +42,7 @@ impl Fixture {
             .await
             .unwrap();
 
-        Fixture { env, program, system: system_ref }
+        Fixture { env, program }
     }
 
     /// Returns an Actor with the signer set to `id`.
@@ -56,10 +51,69 @@ impl Fixture {
         Actor::new(env, self.program.id())
     }
 
-    /// Advance the simulated block clock by `blocks` (1 block = 1 000 ms in gtest).
-    /// Use `CHALLENGE_WINDOW_BLOCKS` (86 400) or `CLAIM_DEADLINE_BLOCKS` (259 200)
+    /// Advance the simulated block clock by `blocks` (1 block = 3 000 ms in gtest).
+    /// Use `CHALLENGE_WINDOW_BLOCKS` (28 800) or `CLAIM_DEADLINE_BLOCKS` (86 400)
     /// from `utils` to hit the exact thresholds defined in constants.rs.
     pub fn spend_blocks(&self, blocks: u32) {
-        self.system.spend_blocks(blocks);
+        let system = self.env.system();
+        system.run_to_block(system.block_height() + blocks);
+    }
+
+    /// Deploys a `MockVft` and returns its program id.
+    pub fn deploy_mock_vft(&self, failing_sender: u64, failing_recipient: u64) -> ActorId {
+        let system = self.env.system();
+        let vft = Program::mock(
+            system,
+            MockVft {
+                failing_sender: actor(failing_sender),
+                failing_recipient: actor(failing_recipient),
+            },
+        );
+        // The first message to a mock program is its init.
+        vft.send_bytes(ADMIN, b"init".to_vec());
+        system.run_next_block();
+        vft.id()
+    }
+}
+
+/// Stand-in for a VFT program: acknowledges `TransferFrom` and `Transfer` with `true`,
+/// except pulls from `failing_sender` and transfers to `failing_recipient`.
+#[derive(Debug, Clone)]
+pub struct MockVft {
+    pub failing_sender: ActorId,
+    pub failing_recipient: ActorId,
+}
+
+impl WasmProgram for MockVft {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        let mut input = payload.as_slice();
+        let (service, method) =
+            <(String, String)>::decode(&mut input).map_err(|_| "Undecodable route")?;
+        let ok = match method.as_str() {
+            "TransferFrom" => {
+                let (from, _to, _value) = <(ActorId, ActorId, U256)>::decode(&mut input)
+                    .map_err(|_| "Undecodable TransferFrom")?;
+                from != self.failing_sender
+            }
+            "Transfer" => {
+                let (to, _value) = <(ActorId, U256)>::decode(&mut input)
+                    .map_err(|_| "Undecodable Transfer")?;
+                to != self.failing_recipient
+            }
+            _ => return Err("Unknown method"),
+        };
+        Ok(Some((service, method, ok).encode()))
+    }
+
+    fn clone_boxed(&self) -> Box<dyn WasmProgram> {
+        Box::new(self.clone())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(Vec::new())
     }
 }
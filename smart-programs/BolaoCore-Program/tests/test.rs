@@ -1,8 +1,13 @@
 use bolao_program::client::{
     service::Service as BolaoSvc, // trait — needed for method dispatch
+    BetOutcome, BolaoProgram, ChallengeStatus, DisputeStatus, MarketKind, MarketParams,
+    MarketSelection, MarketStatus, ParlayStatus, PenaltyWinner, Prediction, PrizeCurve,
     ResultStatus, Score,
 };
-use sails_rs::prelude::*;
+use sails_rs::{
+    client::{Actor, BlockRunMode, GtestEnv},
+    prelude::*,
+};
 
 mod fixture;
 mod utils;
@@ -10,7 +15,7 @@ mod utils;
 use fixture::{actor, Fixture, ADMIN, NEW_ADMIN, ORACLE, STRANGER, USER1, USER2};
 use utils::{
     AWAY_TEAM, BET_5_VARA, BET_10_VARA, CHALLENGE_WINDOW_BLOCKS, CLAIM_DEADLINE_BLOCKS,
-    GROUP_PHASE, HOME_TEAM, KICK_OFF, MIN_BET, ONE_VARA,
+    DISPUTE_BOND, GROUP_PHASE, HOME_TEAM, KICK_OFF, KNOCKOUT_PHASE, MIN_BET, ONE_VARA,
 };
 
 // ── Shared setup helpers ──────────────────────────────────────────────────────
@@ -713,3 +718,2486 @@ async fn sweep_permissionless_by_stranger_after_claim_deadline() {
     assert!(m.dust_swept, "match should be dust_swept after deadline");
     assert_eq!(m.match_prize_pool, 0, "match pool should be zeroed after sweep");
 }
+
+// ── Test 18: points settlement is resumable and cannot be re-run ─────────────
+
+#[tokio::test]
+async fn continue_settlement_guards() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+
+    let score = Score { home: 1, away: 1 };
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, score.clone(), None)
+        .with_value(BET_5_VARA)
+        .await
+        .unwrap();
+
+    // Not finalized yet — nothing to settle.
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .continue_settlement(match_id, 10)
+        .await;
+    assert!(err.is_err(), "continue_settlement before finalization should fail");
+
+    propose_and_finalize(&f, match_id, score).await;
+
+    // Claims open at finalization; points are credited separately.
+    let m = f
+        .program
+        .service("Service")
+        .query_match(match_id)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert!(m.settlement_prepared, "winner stake comes from buckets at finalization");
+    assert_eq!(m.total_winner_stake, m.match_prize_pool);
+    assert!(!m.points_settled, "finalize_result must not touch participants");
+    assert_eq!(m.settlement_cursor, 0);
+
+    // Pending points are derived on query before anything is materialized.
+    let pts = f
+        .program
+        .service("Service")
+        .query_user_points(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(pts, 3, "exact draw in group phase should award 3 points");
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .continue_settlement(match_id, 0)
+        .await;
+    assert!(err.is_err(), "zero-sized batch should be rejected");
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .continue_settlement(match_id, 10)
+        .await
+        .expect("stranger should credit points");
+
+    let m = f
+        .program
+        .service("Service")
+        .query_match(match_id)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert!(m.points_settled, "single batch should cover every participant");
+    assert_eq!(m.settlement_cursor, 1);
+
+    let pts = f
+        .program
+        .service("Service")
+        .query_user_points(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(pts, 3, "materializing must not double-count pending points");
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .continue_settlement(match_id, 10)
+        .await;
+    assert!(err.is_err(), "continue_settlement after completion should fail");
+
+    // Points cannot be credited twice through the per-user path either.
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .sync_points()
+        .await;
+    assert!(err.is_err(), "sync_points with nothing pending should fail");
+}
+
+// ── Test 19: unresolved match expires and bettors are refunded ───────────────
+
+#[tokio::test]
+async fn expired_match_refunds_stake() {
+    let f = Fixture::new().await;
+
+    f.program
+        .service("Service")
+        .register_phase(GROUP_PHASE.to_string(), 0, u64::MAX, 1)
+        .await
+        .unwrap();
+
+    // Shortest allowed resolution period: 1 hour after kick-off.
+    f.program
+        .service("Service")
+        .set_resolution_period(3_600_000)
+        .await
+        .unwrap();
+
+    let kick_off = f.env.system().block_timestamp() + 3_600_000;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            HOME_TEAM.to_string(),
+            AWAY_TEAM.to_string(),
+            kick_off,
+        )
+        .await
+        .unwrap();
+    let match_id = 1;
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, Score { home: 1, away: 0 }, None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    // Deadline not reached yet.
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .expire_match(match_id)
+        .await;
+    assert!(err.is_err(), "expire_match before the resolution deadline should fail");
+
+    // Kick-off + 1h resolution period, at 3 s per block.
+    f.spend_blocks(2 * 1_200 + 1);
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .expire_match(match_id)
+        .await
+        .expect("anyone should expire an unresolved match after the deadline");
+
+    let m = f
+        .program
+        .service("Service")
+        .query_match(match_id)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert!(matches!(m.result, ResultStatus::Expired), "match should be Expired");
+
+    let status = f
+        .program
+        .service("Service")
+        .query_wallet_claim_status(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(status.amount_claimable, m.match_prize_pool, "refund is the match-pool stake");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_refund(match_id)
+        .await
+        .expect("bettor should reclaim stake on expired match");
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .claim_refund(match_id)
+        .await;
+    assert!(err.is_err(), "double refund should be rejected");
+
+    // No result can be proposed for an expired match.
+    f.program
+        .service("Service")
+        .set_oracle_authorized(actor(ORACLE), true)
+        .await
+        .unwrap();
+    let err = f
+        .as_actor(ORACLE)
+        .service("Service")
+        .propose_result(match_id, Score { home: 1, away: 0 }, None)
+        .await;
+    assert!(err.is_err(), "expired match should not accept proposals");
+}
+
+// ── Test 20: bettor dispute freezes finalization until arbitration ───────────
+
+#[tokio::test]
+async fn dispute_freezes_finalization_until_resolved() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, Score { home: 2, away: 0 }, None)
+        .with_value(BET_5_VARA)
+        .await
+        .unwrap();
+
+    propose(&f, match_id, Score { home: 0, away: 0 }).await;
+
+    // Non-bettors cannot dispute.
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .dispute_result(match_id, Score { home: 2, away: 0 }, None)
+        .with_value(DISPUTE_BOND)
+        .await;
+    assert!(err.is_err(), "non-bettor should not dispute");
+
+    // Wrong bond is rejected.
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .dispute_result(match_id, Score { home: 2, away: 0 }, None)
+        .with_value(ONE_VARA)
+        .await;
+    assert!(err.is_err(), "dispute without the exact bond should fail");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .dispute_result(match_id, Score { home: 2, away: 0 }, None)
+        .with_value(DISPUTE_BOND)
+        .await
+        .expect("bettor should dispute within challenge window");
+
+    // Window expires but the open dispute blocks finalization.
+    f.spend_blocks(CHALLENGE_WINDOW_BLOCKS + 1);
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .finalize_result(match_id)
+        .await;
+    assert!(err.is_err(), "finalize_result should fail while a dispute is open");
+
+    // Only an arbiter can resolve.
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .resolve_dispute(match_id, 0, false)
+        .await;
+    assert!(err.is_err(), "stranger should not resolve disputes");
+
+    let before = f.program.service("Service").query_state().query().unwrap();
+
+    f.program
+        .service("Service")
+        .resolve_dispute(match_id, 0, false)
+        .await
+        .expect("admin should reject the dispute");
+
+    let disputes = f
+        .program
+        .service("Service")
+        .query_disputes(match_id)
+        .query()
+        .unwrap();
+    assert_eq!(disputes.len(), 1);
+    assert_eq!(disputes[0].status, DisputeStatus::Rejected);
+
+    // Rejected bond flows to the final prize pool.
+    let after = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(
+        after.final_prize_accumulated,
+        before.final_prize_accumulated + DISPUTE_BOND
+    );
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .finalize_result(match_id)
+        .await
+        .expect("finalization should resume once disputes are resolved");
+}
+
+// ── Test 21: per-phase challenge window and claim deadline ───────────────────
+
+#[tokio::test]
+async fn phase_windows_override_defaults() {
+    let f = Fixture::new().await;
+    f.program
+        .service("Service")
+        .register_phase(GROUP_PHASE.to_string(), 0, u64::MAX, 1)
+        .await
+        .unwrap();
+
+    // Bounds and access control.
+    let err = f
+        .program
+        .service("Service")
+        .set_phase_windows(GROUP_PHASE.to_string(), 0, 86_400_000)
+        .await;
+    assert!(err.is_err(), "challenge window below minimum should be rejected");
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .set_phase_windows(GROUP_PHASE.to_string(), 172_800_000, 86_400_000)
+        .await;
+    assert!(err.is_err(), "non-admin should not set phase windows");
+
+    // 48h challenge window, 24h claim deadline for the group stage.
+    f.program
+        .service("Service")
+        .set_phase_windows(GROUP_PHASE.to_string(), 172_800_000, 86_400_000)
+        .await
+        .expect("admin should set phase windows");
+
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.phases[0].challenge_window_ms, 172_800_000);
+    assert_eq!(state.phases[0].claim_deadline_ms, 86_400_000);
+
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            HOME_TEAM.to_string(),
+            AWAY_TEAM.to_string(),
+            KICK_OFF,
+        )
+        .await
+        .unwrap();
+    let match_id = 1;
+
+    let err = f
+        .program
+        .service("Service")
+        .set_phase_windows(GROUP_PHASE.to_string(), 86_400_000, 259_200_000)
+        .await;
+    assert!(err.is_err(), "phase windows are locked once the phase has matches");
+
+    propose(&f, match_id, Score { home: 1, away: 0 }).await;
+
+    // 24h is not enough under the phase's 48h window.
+    f.spend_blocks(CHALLENGE_WINDOW_BLOCKS + 1);
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .finalize_result(match_id)
+        .await;
+    assert!(err.is_err(), "finalize_result should honour the phase challenge window");
+
+    f.spend_blocks(CHALLENGE_WINDOW_BLOCKS);
+    f.as_actor(STRANGER)
+        .service("Service")
+        .finalize_result(match_id)
+        .await
+        .expect("finalize_result should succeed after the phase window");
+}
+
+// ── Test 22: referrer earns a slice of referred users' protocol fees ─────────
+
+#[tokio::test]
+async fn referral_fee_sharing() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+
+    f.as_actor(USER1)
+        .service("Service")
+        .register_referrer("brazil10".to_string())
+        .await
+        .expect("USER1 should register a referral code");
+
+    // Code is unique.
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .register_referrer("brazil10".to_string())
+        .await;
+    assert!(err.is_err(), "duplicate referral code should be rejected");
+
+    // Self-referral is rejected.
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .set_referrer("brazil10".to_string())
+        .await;
+    assert!(err.is_err(), "self-referral should be rejected");
+
+    f.as_actor(USER2)
+        .service("Service")
+        .set_referrer("brazil10".to_string())
+        .await
+        .expect("USER2 should set a referrer before betting");
+
+    f.as_actor(USER2)
+        .service("Service")
+        .place_bet(match_id, Score { home: 1, away: 0 }, None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    // 5% protocol fee on 10 VARA = 0.5 VARA; default 20% of it goes to the referrer.
+    let stats = f
+        .program
+        .service("Service")
+        .query_referral_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(stats.code, Some("brazil10".to_string()));
+    assert_eq!(stats.referred_users, 1);
+    assert_eq!(stats.volume, BET_10_VARA);
+    assert_eq!(stats.earned, ONE_VARA / 10);
+    assert_eq!(stats.claimable, ONE_VARA / 10);
+
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.protocol_fee_accumulated, ONE_VARA / 2 - ONE_VARA / 10);
+
+    // Referrer cannot be changed once set.
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .set_referrer("brazil10".to_string())
+        .await;
+    assert!(err.is_err(), "referrer is one-time");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_referral_rewards()
+        .await
+        .expect("referrer should claim earned fees");
+
+    let stats = f
+        .program
+        .service("Service")
+        .query_referral_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(stats.claimable, 0);
+    assert_eq!(stats.earned, ONE_VARA / 10);
+}
+
+// ── Test 23: payment mode is chosen before the first bet ─────────────────────
+
+#[tokio::test]
+async fn payment_token_mode_guards() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    let token = actor(STRANGER);
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .set_payment_token(Some(token))
+        .await;
+    assert!(err.is_err(), "only admin can choose the payment mode");
+
+    f.program
+        .service("Service")
+        .set_payment_token(Some(token))
+        .await
+        .expect("admin should enable token mode before any bet");
+
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.payment_token, Some(token));
+
+    // Native bets are disabled in token mode.
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, Score { home: 1, away: 0 }, None)
+        .with_value(BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "native bet should be rejected in token mode");
+
+    // Token bets must not carry native value.
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_bet_with_token(match_id, Score { home: 1, away: 0 }, None, BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "token bet with attached value should be rejected");
+
+    // Back to native mode, still allowed while no bet exists.
+    f.program
+        .service("Service")
+        .set_payment_token(None)
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_bet_with_token(match_id, Score { home: 1, away: 0 }, None, BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "token bet should be rejected in native mode");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, Score { home: 1, away: 0 }, None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    let err = f
+        .program
+        .service("Service")
+        .set_payment_token(Some(token))
+        .await;
+    assert!(err.is_err(), "payment mode is locked after the first bet");
+}
+
+// ── Test 24: free-to-play predictions and the sponsor pool ───────────────────
+
+#[tokio::test]
+async fn free_predictions_and_sponsor_pool() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    let final_score = Score { home: 1, away: 0 };
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, final_score.clone(), None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    // Free predictions carry no value.
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .place_prediction(match_id, final_score.clone(), None)
+        .with_value(MIN_BET)
+        .await;
+    assert!(err.is_err(), "free prediction with value should be rejected");
+
+    f.as_actor(USER2)
+        .service("Service")
+        .place_prediction(match_id, final_score.clone(), None)
+        .await
+        .expect("USER2 should place a free prediction");
+    f.as_actor(STRANGER)
+        .service("Service")
+        .place_prediction(match_id, Score { home: 0, away: 1 }, None)
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .place_prediction(match_id, final_score.clone(), None)
+        .await;
+    assert!(err.is_err(), "one free prediction per match");
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .fund_sponsor_pool(BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await
+        .expect("anyone can sponsor the free-to-play pool");
+
+    // The match pool only holds USER1's staked 85%.
+    let m = f
+        .program
+        .service("Service")
+        .query_match(match_id)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert_eq!(m.match_prize_pool, BET_10_VARA * 85 / 100);
+    assert_eq!(m.free_participants, vec![actor(USER2), actor(STRANGER)]);
+
+    propose_and_finalize(&f, match_id, final_score).await;
+
+    let free_points = f
+        .program
+        .service("Service")
+        .query_free_points(actor(USER2))
+        .query()
+        .unwrap();
+    assert_eq!(free_points, 3, "exact score on a weight-1 match");
+    let points = f
+        .program
+        .service("Service")
+        .query_user_points(actor(USER2))
+        .query()
+        .unwrap();
+    assert_eq!(points, 0, "free points stay off the staked leaderboard");
+
+    let err = f.program.service("Service").finalize_free_prize_pool().await;
+    assert!(err.is_err(), "free prize needs every match's points credited");
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .continue_settlement(match_id, 10)
+        .await
+        .unwrap();
+
+    let err = f.program.service("Service").finalize_free_prize_pool().await;
+    assert!(err.is_err(), "free predictions settle on their own cursor");
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .continue_free_settlement(match_id, 10)
+        .await
+        .unwrap();
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .continue_free_settlement(match_id, 10)
+        .await;
+    assert!(err.is_err(), "free settlement after completion should fail");
+
+    let leaderboard = f
+        .program
+        .service("Service")
+        .query_free_leaderboard()
+        .query()
+        .unwrap();
+    assert_eq!(leaderboard, vec![(actor(USER2), 3), (actor(STRANGER), 0)]);
+
+    f.program
+        .service("Service")
+        .finalize_free_prize_pool()
+        .await
+        .expect("admin should finalize the sponsor pool");
+
+    f.as_actor(USER2)
+        .service("Service")
+        .claim_free_prize()
+        .await
+        .expect("top free predictor should claim");
+
+    let err = f.as_actor(USER2).service("Service").claim_free_prize().await;
+    assert!(err.is_err(), "free prize is claimed once");
+
+    let err = f.as_actor(USER1).service("Service").claim_free_prize().await;
+    assert!(err.is_err(), "staked-only bettors are not on the free leaderboard");
+
+    // 1st gets 45%, 2nd 25%; the unfilled places (30%) go to the leader as dust.
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.sponsor_pool, BET_10_VARA / 4);
+}
+
+// ── Test 25: per-user stats and paged bet history ────────────────────────────
+
+#[tokio::test]
+async fn user_stats_and_history() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    let final_score = Score { home: 1, away: 0 };
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, final_score.clone(), None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    propose_and_finalize(&f, match_id, final_score).await;
+
+    // Stats include points not yet credited.
+    let stats = f
+        .program
+        .service("Service")
+        .query_user_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(stats.bets, 1);
+    assert_eq!(stats.exact_hits, 1);
+    assert_eq!(stats.correct_outcomes, 0);
+    assert_eq!(stats.best_streak, 1);
+    assert_eq!(stats.phase_points, vec![(GROUP_PHASE.to_string(), 3)]);
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_match_reward(match_id)
+        .await
+        .unwrap();
+
+    // Sole winner takes the whole 85% match pool.
+    let pool = BET_10_VARA * 85 / 100;
+    let stats = f
+        .program
+        .service("Service")
+        .query_user_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(stats.total_staked, BET_10_VARA);
+    assert_eq!(stats.total_won, pool);
+    assert_eq!(stats.net_pnl, pool as i128 - BET_10_VARA as i128);
+    assert_eq!(stats.exact_hits, 1, "crediting must not double count");
+    assert_eq!(stats.phase_points, vec![(GROUP_PHASE.to_string(), 3)]);
+
+    let history = f
+        .program
+        .service("Service")
+        .query_user_history(actor(USER1), 0, 10)
+        .query()
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].match_id, match_id);
+    assert_eq!(history[0].outcome, BetOutcome::ExactScore);
+    assert_eq!(history[0].points, 3);
+    assert_eq!(history[0].payout, pool);
+    assert!(history[0].claimed);
+
+    let page = f
+        .program
+        .service("Service")
+        .query_user_history(actor(USER1), 1, 10)
+        .query()
+        .unwrap();
+    assert!(page.is_empty());
+}
+
+// ── Test 26: solvency report balances liabilities against the program balance ──
+
+#[tokio::test]
+async fn solvency_report_tracks_liabilities() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+
+    let before = f.program.service("Service").query_solvency().query().unwrap();
+    assert_eq!(before.total_liabilities, 0);
+    assert_eq!(before.deficit, 0);
+    assert_eq!(before.payment_token, None);
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, Score { home: 1, away: 0 }, None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    // 85% open match pool + 10% final prize + 5% protocol fee = the whole bet.
+    let report = f.program.service("Service").query_solvency().query().unwrap();
+    assert_eq!(report.unswept_match_pools, BET_10_VARA * 85 / 100);
+    assert_eq!(report.final_prize_accumulated, BET_10_VARA / 10);
+    assert_eq!(report.protocol_fee_accumulated, BET_10_VARA / 20);
+    assert_eq!(report.total_liabilities, BET_10_VARA);
+    assert_eq!(report.balance, before.balance + BET_10_VARA);
+    assert_eq!(report.surplus, before.surplus);
+    assert_eq!(report.deficit, 0);
+
+    propose_and_finalize(&f, match_id, Score { home: 1, away: 0 }).await;
+
+    let report = f.program.service("Service").query_solvency().query().unwrap();
+    assert_eq!(report.unswept_match_pools, 0);
+    assert_eq!(report.unclaimed_match_rewards, BET_10_VARA * 85 / 100);
+    assert_eq!(report.total_liabilities, BET_10_VARA);
+}
+
+// ── Test 27: configurable prize curve, locked after the first bet ────────────
+
+#[tokio::test]
+async fn prize_curve_configuration() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    let final_score = Score { home: 2, away: 2 };
+
+    let err = f
+        .program
+        .service("Service")
+        .set_prize_curve(PrizeCurve::Table(vec![6_000, 3_000]))
+        .await;
+    assert!(err.is_err(), "shares must sum to 100%");
+
+    let err = f
+        .program
+        .service("Service")
+        .set_prize_curve(PrizeCurve::Table(vec![10_000, 0]))
+        .await;
+    assert!(err.is_err(), "zero shares are rejected");
+
+    let err = f
+        .program
+        .service("Service")
+        .set_prize_curve(PrizeCurve::Table(vec![u128::MAX, 10_001]))
+        .await;
+    assert!(err.is_err(), "overflowing shares must not wrap to 100%");
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .set_prize_curve(PrizeCurve::ProportionalTopN(3))
+        .await;
+    assert!(err.is_err(), "only admin can set the curve");
+
+    f.program
+        .service("Service")
+        .set_prize_curve(PrizeCurve::Table(vec![6_000, 4_000]))
+        .await
+        .expect("two-place table should be accepted");
+
+    f.as_actor(USER2)
+        .service("Service")
+        .place_prediction(match_id, final_score.clone(), None)
+        .await
+        .unwrap();
+    f.as_actor(STRANGER)
+        .service("Service")
+        .place_prediction(match_id, Score { home: 0, away: 3 }, None)
+        .await
+        .unwrap();
+    f.as_actor(STRANGER)
+        .service("Service")
+        .fund_sponsor_pool(BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, final_score.clone(), None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    let err = f
+        .program
+        .service("Service")
+        .set_prize_curve(PrizeCurve::ProportionalTopN(3))
+        .await;
+    assert!(err.is_err(), "curve is locked after the first bet");
+
+    propose_and_finalize(&f, match_id, final_score).await;
+    f.as_actor(STRANGER)
+        .service("Service")
+        .continue_free_settlement(match_id, 10)
+        .await
+        .unwrap();
+
+    f.program
+        .service("Service")
+        .finalize_free_prize_pool()
+        .await
+        .unwrap();
+
+    // Both places are filled, so the whole pool is allocated: 60% / 40%.
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.sponsor_pool, BET_10_VARA);
+    assert_eq!(state.prize_curve, PrizeCurve::Table(vec![6_000, 4_000]));
+
+    f.as_actor(USER2)
+        .service("Service")
+        .claim_free_prize()
+        .await
+        .unwrap();
+
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.sponsor_pool, BET_10_VARA * 4 / 10);
+}
+
+// ── Test 28: phase prize funded from the final prize cut ─────────────────────
+
+#[tokio::test]
+async fn phase_prize_flow() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    let final_score = Score { home: 1, away: 0 };
+
+    let err = f
+        .program
+        .service("Service")
+        .set_phase_prize_bps(1_500)
+        .await;
+    assert!(err.is_err(), "phase prize cannot exceed the final prize cut");
+
+    f.program
+        .service("Service")
+        .set_phase_prize_bps(500)
+        .await
+        .expect("admin should route 5% of each bet to its phase prize");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, final_score.clone(), None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .place_bet(match_id, Score { home: 0, away: 0 }, None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    // Each bet: 10% final prize cut, half of it to the phase prize.
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.final_prize_accumulated, ONE_VARA);
+    assert_eq!(state.phase_prize_pools, vec![(GROUP_PHASE.to_string(), ONE_VARA)]);
+
+    propose_and_finalize(&f, match_id, final_score).await;
+
+    let err = f
+        .program
+        .service("Service")
+        .finalize_phase_prize(GROUP_PHASE.to_string())
+        .await;
+    assert!(err.is_err(), "phase points must be credited first");
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .continue_settlement(match_id, 10)
+        .await
+        .unwrap();
+
+    let leaderboard = f
+        .program
+        .service("Service")
+        .query_phase_leaderboard(GROUP_PHASE.to_string())
+        .query()
+        .unwrap();
+    assert_eq!(leaderboard, vec![(actor(USER1), 3), (actor(USER2), 0)]);
+
+    f.program
+        .service("Service")
+        .finalize_phase_prize(GROUP_PHASE.to_string())
+        .await
+        .unwrap();
+
+    // Default curve: 45% + 25% allocated, the unfilled 30% returns to the final prize.
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.final_prize_accumulated, ONE_VARA + ONE_VARA * 3 / 10);
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_phase_prize(GROUP_PHASE.to_string())
+        .await
+        .expect("phase leader should claim");
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .claim_phase_prize(GROUP_PHASE.to_string())
+        .await;
+    assert!(err.is_err(), "phase prize is claimed once");
+
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.phase_prize_pools, vec![(GROUP_PHASE.to_string(), ONE_VARA / 4)]);
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .sweep_phase_prize(GROUP_PHASE.to_string())
+        .await;
+    assert!(err.is_err(), "sweep waits for the claim deadline");
+
+    f.spend_blocks(CLAIM_DEADLINE_BLOCKS + 1);
+
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .claim_phase_prize(GROUP_PHASE.to_string())
+        .await;
+    assert!(err.is_err(), "phase prize claims close at the deadline");
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .sweep_phase_prize(GROUP_PHASE.to_string())
+        .await
+        .expect("anyone sweeps after the deadline");
+
+    // The final prize is still open, so USER2's unclaimed 25% joins it.
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.phase_prize_pools, vec![(GROUP_PHASE.to_string(), 0)]);
+    assert_eq!(state.final_prize_accumulated, ONE_VARA + ONE_VARA * 11 / 20);
+}
+
+// ── Test 29: exact-score jackpot rolls over until someone hits it ────────────
+
+#[tokio::test]
+async fn jackpot_rolls_over_to_next_match() {
+    let f = Fixture::new().await;
+    let first = setup_phase_and_match(&f).await;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            AWAY_TEAM.to_string(),
+            HOME_TEAM.to_string(),
+            KICK_OFF + 1_000,
+        )
+        .await
+        .unwrap();
+    let second = 2;
+
+    f.program
+        .service("Service")
+        .set_jackpot_bps(500)
+        .await
+        .expect("admin should enable a 5% jackpot cut");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(first, Score { home: 1, away: 0 }, None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .place_bet(second, Score { home: 2, away: 1 }, None)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    let err = f.program.service("Service").set_jackpot_bps(100).await;
+    assert!(err.is_err(), "jackpot share is locked after the first bet");
+
+    // 5% fee, 10% final prize, 5% jackpot, 80% match pool.
+    let m = f
+        .program
+        .service("Service")
+        .query_match(first)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert_eq!(m.match_prize_pool, BET_10_VARA * 80 / 100);
+    assert_eq!(m.jackpot, BET_10_VARA / 20);
+
+    // Right outcome, wrong score: nobody hits the first jackpot.
+    propose_and_finalize(&f, first, Score { home: 2, away: 0 }).await;
+
+    let jackpot = f.program.service("Service").query_jackpot().query().unwrap();
+    assert_eq!(jackpot.next_match_id, Some(second));
+    assert_eq!(jackpot.next_jackpot, ONE_VARA);
+    assert_eq!(jackpot.total_pending, ONE_VARA);
+
+    propose_and_finalize(&f, second, Score { home: 2, away: 1 }).await;
+
+    f.as_actor(USER2)
+        .service("Service")
+        .claim_match_reward(second)
+        .await
+        .unwrap();
+
+    // Sole exact hitter: whole match pool plus both jackpots.
+    let history = f
+        .program
+        .service("Service")
+        .query_user_history(actor(USER2), 0, 10)
+        .query()
+        .unwrap();
+    assert_eq!(history[0].payout, BET_10_VARA * 80 / 100 + ONE_VARA);
+
+    let jackpot = f.program.service("Service").query_jackpot().query().unwrap();
+    assert_eq!(jackpot.next_match_id, None);
+    assert_eq!(jackpot.total_pending, 0);
+}
+
+// ── Test 30: parlays pay out only when every leg is right ────────────────────
+
+#[tokio::test]
+async fn parlay_settles_and_pays_from_pool() {
+    let f = Fixture::new().await;
+    let first = setup_phase_and_match(&f).await;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            AWAY_TEAM.to_string(),
+            HOME_TEAM.to_string(),
+            KICK_OFF + 1_000,
+        )
+        .await
+        .unwrap();
+    let second = 2;
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_parlay(vec![(first, Score { home: 1, away: 0 }, None)], BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "a parlay needs at least two legs");
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_parlay(
+            vec![
+                (first, Score { home: 1, away: 0 }, None),
+                (first, Score { home: 2, away: 0 }, None),
+            ],
+            BET_10_VARA,
+        )
+        .with_value(BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "legs must be on distinct matches");
+
+    // USER1 gets both outcomes right; USER2 misses the second match.
+    f.as_actor(USER1)
+        .service("Service")
+        .place_parlay(
+            vec![
+                (first, Score { home: 1, away: 0 }, None),
+                (second, Score { home: 2, away: 1 }, None),
+            ],
+            BET_10_VARA,
+        )
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .place_parlay(
+            vec![
+                (first, Score { home: 1, away: 0 }, None),
+                (second, Score { home: 0, away: 0 }, None),
+            ],
+            BET_10_VARA,
+        )
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    // 5% fee and 10% final prize, 85% into the parlay pool.
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(state.parlay_pool, 2 * BET_10_VARA * 85 / 100);
+
+    propose_and_finalize(&f, first, Score { home: 2, away: 0 }).await;
+
+    let err = f.program.service("Service").settle_parlay(0).await;
+    assert!(err.is_err(), "settlement waits for the last leg");
+
+    propose_and_finalize(&f, second, Score { home: 3, away: 1 }).await;
+
+    for parlay_id in [0, 1] {
+        f.as_actor(STRANGER)
+            .service("Service")
+            .settle_parlay(parlay_id)
+            .await
+            .expect("settling a parlay is permissionless");
+    }
+
+    let err = f.as_actor(USER2).service("Service").claim_parlay(1).await;
+    assert!(err.is_err(), "a lost parlay has nothing to claim");
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .finalize_parlay_pool()
+        .await
+        .unwrap();
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_parlay(0)
+        .await
+        .unwrap();
+
+    let won = f
+        .program
+        .service("Service")
+        .query_parlay(0)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert_eq!(won.status, ParlayStatus::Won);
+    assert_eq!(won.live_legs, 2);
+    assert_eq!(won.paid_out, 2 * BET_10_VARA * 85 / 100);
+
+    let lost = f
+        .program
+        .service("Service")
+        .query_parlays_by_user(actor(USER2))
+        .query()
+        .unwrap();
+    assert_eq!(lost[0].status, ParlayStatus::Lost);
+}
+
+// ── Test 31: side markets settle from the final score ────────────────────────
+
+#[tokio::test]
+async fn over_under_market_pays_and_pushes() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+
+    let err = f
+        .program
+        .service("Service")
+        .create_market(match_id, MarketKind::OverUnder, MarketParams { line: 275 })
+        .await;
+    assert!(err.is_err(), "quarter lines are not supported");
+
+    let err = f
+        .program
+        .service("Service")
+        .create_market(match_id, MarketKind::BothTeamsToScore, MarketParams { line: 50 })
+        .await;
+    assert!(err.is_err(), "both-teams-to-score takes no line");
+
+    // Market 0: over/under 2.5; market 1: over/under 3 (pushes on three goals).
+    for line in [250, 300] {
+        f.program
+            .service("Service")
+            .create_market(match_id, MarketKind::OverUnder, MarketParams { line })
+            .await
+            .unwrap();
+    }
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_market_bet(0, MarketSelection::Yes, BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "selection must belong to the market kind");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_market_bet(0, MarketSelection::Over, BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .place_market_bet(0, MarketSelection::Under, BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+    f.as_actor(USER1)
+        .service("Service")
+        .place_market_bet(1, MarketSelection::Under, BET_5_VARA)
+        .with_value(BET_5_VARA)
+        .await
+        .unwrap();
+
+    propose_and_finalize(&f, match_id, Score { home: 2, away: 1 }).await;
+
+    for market_id in [0, 1] {
+        f.as_actor(STRANGER)
+            .service("Service")
+            .settle_market(market_id)
+            .await
+            .expect("settling a market is permissionless");
+    }
+
+    let err = f.as_actor(USER2).service("Service").claim_market_reward(0).await;
+    assert!(err.is_err(), "under 2.5 lost on three goals");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_market_reward(0)
+        .await
+        .unwrap();
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_market_reward(1)
+        .await
+        .unwrap();
+
+    // Sole winner takes the whole 85% pool; the push refunds the pool stake.
+    let won = f
+        .program
+        .service("Service")
+        .query_market_bet(0, actor(USER1))
+        .query()
+        .unwrap()
+        .unwrap();
+    assert_eq!(won.paid_out, 2 * BET_10_VARA * 85 / 100);
+
+    let markets = f
+        .program
+        .service("Service")
+        .query_markets_by_match(match_id)
+        .query()
+        .unwrap();
+    let pushed = markets.iter().find(|m| m.market_id == 1).unwrap();
+    assert_eq!(pushed.status, MarketStatus::Void);
+    assert_eq!(pushed.total_claimed, BET_5_VARA * 85 / 100);
+}
+
+// ── Test 32: head-to-head challenges ─────────────────────────────────────────
+
+#[tokio::test]
+async fn head_to_head_challenge_flow() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    let home_win = Prediction { score: Score { home: 1, away: 0 }, penalty_winner: None };
+    let away_win = Prediction { score: Score { home: 0, away: 1 }, penalty_winner: None };
+
+    // Challenge 0: USER1 vs USER2 only. Challenge 1: open to anyone.
+    f.as_actor(USER1)
+        .service("Service")
+        .create_challenge(match_id, Some(actor(USER2)), home_win.clone(), BET_10_VARA)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+    f.as_actor(USER1)
+        .service("Service")
+        .create_challenge(match_id, None, home_win.clone(), BET_5_VARA)
+        .with_value(BET_5_VARA)
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .accept_challenge(0, away_win.clone())
+        .with_value(BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "challenge is reserved for USER2");
+
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .accept_challenge(0, home_win)
+        .with_value(BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "counter prediction must differ");
+
+    f.as_actor(USER2)
+        .service("Service")
+        .accept_challenge(0, away_win)
+        .with_value(BET_10_VARA)
+        .await
+        .unwrap();
+
+    f.as_actor(USER1)
+        .service("Service")
+        .refund_challenge(1)
+        .await
+        .expect("creator can cancel an unaccepted challenge");
+
+    let err = f.as_actor(STRANGER).service("Service").settle_challenge(0).await;
+    assert!(err.is_err(), "settlement waits for the result");
+
+    propose_and_finalize(&f, match_id, Score { home: 2, away: 0 }).await;
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .settle_challenge(0)
+        .await
+        .expect("settling a challenge is permissionless");
+
+    let challenges = f
+        .program
+        .service("Service")
+        .query_challenges_by_user(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(challenges.len(), 2);
+    assert_eq!(challenges[0].status, ChallengeStatus::Settled);
+    assert_eq!(challenges[0].winner, Some(actor(USER1)));
+    assert_eq!(challenges[1].status, ChallengeStatus::Refunded);
+
+    // Winner takes both stakes minus the 5% protocol fee and the 10% final prize cut.
+    let stats = f
+        .program
+        .service("Service")
+        .query_user_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(stats.total_won, 2 * BET_10_VARA * 85 / 100);
+    assert_eq!(stats.total_refunded, BET_5_VARA);
+}
+
+// ── Test 33: survivor pool ends with the last entrant standing ───────────────
+
+#[tokio::test]
+async fn survivor_last_entrant_takes_pool() {
+    let f = Fixture::new().await;
+    let first = setup_phase_and_match(&f).await;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            AWAY_TEAM.to_string(),
+            HOME_TEAM.to_string(),
+            KICK_OFF + 1_000,
+        )
+        .await
+        .unwrap();
+    let second = 2;
+
+    for match_ids in [vec![first], vec![second]] {
+        f.program
+            .service("Service")
+            .register_survivor_round(match_ids)
+            .await
+            .unwrap();
+    }
+
+    let err = f
+        .program
+        .service("Service")
+        .register_survivor_round(vec![first])
+        .await;
+    assert!(err.is_err(), "a match belongs to one round only");
+
+    for user in [USER1, USER2] {
+        f.as_actor(user)
+            .service("Service")
+            .join_survivor()
+            .with_value(MIN_BET)
+            .await
+            .unwrap();
+    }
+
+    f.as_actor(USER1)
+        .service("Service")
+        .survivor_pick(0, HOME_TEAM.to_string())
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .survivor_pick(0, AWAY_TEAM.to_string())
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .survivor_pick(1, HOME_TEAM.to_string())
+        .await;
+    assert!(err.is_err(), "a team can be used only once");
+
+    let err = f.as_actor(STRANGER).service("Service").settle_survivor_round(0, 10).await;
+    assert!(err.is_err(), "round waits for its matches");
+
+    propose_and_finalize(&f, first, Score { home: 2, away: 0 }).await;
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .settle_survivor_round(0, 10)
+        .await
+        .expect("settling a survivor round is permissionless");
+
+    let status = f.program.service("Service").query_survivor_status().query().unwrap();
+    assert_eq!(status.entrants, 2);
+    assert_eq!(status.alive, 1);
+    assert!(status.finished);
+    assert_eq!(status.winners, 1);
+
+    let err = f.as_actor(USER2).service("Service").claim_survivor_prize().await;
+    assert!(err.is_err(), "eliminated entrants win nothing");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_survivor_prize()
+        .await
+        .unwrap();
+
+    let stats = f
+        .program
+        .service("Service")
+        .query_user_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(stats.total_won, 2 * MIN_BET * 85 / 100);
+}
+
+// ── Test 34: knockout bracket picks score as slots are decided ───────────────
+
+#[tokio::test]
+async fn bracket_scores_advancing_teams() {
+    let f = Fixture::new().await;
+
+    for (phase, weight) in [("Round of 32", 2), (KNOCKOUT_PHASE, 4)] {
+        f.program
+            .service("Service")
+            .register_phase(phase.to_string(), 0, u64::MAX, weight)
+            .await
+            .unwrap();
+    }
+    for (phase, home, away, kick_off) in [
+        ("Round of 32", HOME_TEAM, AWAY_TEAM, KICK_OFF),
+        ("Round of 32", "Spain", "France", KICK_OFF),
+        (KNOCKOUT_PHASE, HOME_TEAM, "France", KICK_OFF + 1_000),
+    ] {
+        f.program
+            .service("Service")
+            .register_match(phase.to_string(), home.to_string(), away.to_string(), kick_off)
+            .await
+            .unwrap();
+    }
+
+    // Slots 0 and 1 are the Round of 32 ties; slot 2 is fed by their winners.
+    for (match_id, feeders) in [(Some(1), None), (Some(2), None), (None, Some((0, 1)))] {
+        f.program
+            .service("Service")
+            .register_bracket_slot(match_id, feeders)
+            .await
+            .unwrap();
+    }
+
+    let picks = |teams: [&str; 3]| teams.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .submit_bracket(picks([HOME_TEAM, "France", "Spain"]))
+        .await;
+    assert!(err.is_err(), "slot 2 pick must come from its feeder picks");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .submit_bracket(picks([HOME_TEAM, "France", HOME_TEAM]))
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .submit_bracket(picks([AWAY_TEAM, "France", "France"]))
+        .await
+        .unwrap();
+
+    f.program
+        .service("Service")
+        .assign_bracket_match(2, 3)
+        .await
+        .expect("later slot gets its match once registered");
+
+    propose_and_finalize(&f, 1, Score { home: 2, away: 0 }).await;
+    propose_and_finalize(&f, 2, Score { home: 0, away: 1 }).await;
+    propose_and_finalize(&f, 3, Score { home: 2, away: 1 }).await;
+
+    // Round weight per correct advancing team: 2 + 2 + 4 and 2.
+    let points = |user| f.program.service("Service").query_user_points(actor(user)).query();
+    assert_eq!(points(USER1).unwrap(), 8);
+    assert_eq!(points(USER2).unwrap(), 2);
+
+    for slot_id in 0..3 {
+        f.as_actor(STRANGER)
+            .service("Service")
+            .continue_bracket_scoring(slot_id, 10)
+            .await
+            .expect("bracket scoring is permissionless");
+    }
+
+    let entry = f
+        .program
+        .service("Service")
+        .query_bracket_entry(actor(USER1))
+        .query()
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.points, 8);
+    assert_eq!(points(USER1).unwrap(), 8, "credited points replace pending ones");
+
+    let bracket = f.program.service("Service").query_bracket().query().unwrap();
+    assert_eq!(bracket[2].winner, Some(HOME_TEAM.to_string()));
+    assert!(bracket.iter().all(|slot| slot.scored));
+}
+
+// ── Test 35: group standings picks earn placement bonuses ────────────────────
+
+#[tokio::test]
+async fn group_standings_award_placement_bonus() {
+    let f = Fixture::new().await;
+    setup_phase_and_match(&f).await;
+    for (home, away) in [(HOME_TEAM, "Spain"), (AWAY_TEAM, "Spain")] {
+        f.program
+            .service("Service")
+            .register_match(GROUP_PHASE.to_string(), home.to_string(), away.to_string(), KICK_OFF)
+            .await
+            .unwrap();
+    }
+    f.program
+        .service("Service")
+        .register_group("Group A".to_string(), vec![1, 2, 3])
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .submit_group_pick("Group A".to_string(), HOME_TEAM.to_string(), "France".to_string())
+        .await;
+    assert!(err.is_err(), "picks must be teams of the group");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .submit_group_pick("Group A".to_string(), HOME_TEAM.to_string(), AWAY_TEAM.to_string())
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .submit_group_pick("Group A".to_string(), AWAY_TEAM.to_string(), HOME_TEAM.to_string())
+        .await
+        .unwrap();
+
+    propose_and_finalize(&f, 1, Score { home: 1, away: 1 }).await;
+    propose_and_finalize(&f, 2, Score { home: 2, away: 0 }).await;
+
+    let err = f.program.service("Service").finalize_group("Group A".to_string()).await;
+    assert!(err.is_err(), "group cannot finalize with a match outstanding");
+
+    propose_and_finalize(&f, 3, Score { home: 1, away: 0 }).await;
+
+    // Brazil and Germany both on 4 points; Brazil ahead on goal difference.
+    f.as_actor(STRANGER)
+        .service("Service")
+        .finalize_group("Group A".to_string())
+        .await
+        .expect("group finalization is permissionless");
+    let table = f
+        .program
+        .service("Service")
+        .query_group_table("Group A".to_string())
+        .query()
+        .unwrap();
+    let order: Vec<_> = table.iter().map(|r| (r.team.as_str(), r.points)).collect();
+    assert_eq!(order, vec![(HOME_TEAM, 4), (AWAY_TEAM, 4), ("Spain", 0)]);
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .continue_group_scoring("Group A".to_string(), 10)
+        .await
+        .unwrap();
+
+    let points = |user| f.program.service("Service").query_user_points(actor(user)).query();
+    assert_eq!(points(USER1).unwrap(), 8, "winner and runner-up both right");
+    assert_eq!(points(USER2).unwrap(), 0);
+
+    let group = f
+        .program
+        .service("Service")
+        .query_group("Group A".to_string())
+        .query()
+        .unwrap()
+        .unwrap();
+    assert!(group.finalized && group.scored && !group.void);
+}
+
+// ── Test 36: podium derived from the Final and third-place match ─────────────
+
+#[tokio::test]
+async fn podium_finalizes_from_match_results() {
+    let f = Fixture::new().await;
+
+    for (phase, weight) in [("Round of 32", 2), (KNOCKOUT_PHASE, 4)] {
+        f.program
+            .service("Service")
+            .register_phase(phase.to_string(), 0, u64::MAX, weight)
+            .await
+            .unwrap();
+    }
+    // Match 1 sets the podium lock; 2 is the Final, 3 the third-place match.
+    for (phase, home, away) in [
+        ("Round of 32", "Italy", "Japan"),
+        (KNOCKOUT_PHASE, HOME_TEAM, AWAY_TEAM),
+        (KNOCKOUT_PHASE, "Spain", "France"),
+    ] {
+        f.program
+            .service("Service")
+            .register_match(phase.to_string(), home.to_string(), away.to_string(), KICK_OFF)
+            .await
+            .unwrap();
+    }
+
+    f.as_actor(USER1)
+        .service("Service")
+        .submit_podium_pick(AWAY_TEAM.to_string(), HOME_TEAM.to_string(), "Spain".to_string())
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .set_podium_matches(2, 3)
+        .await;
+    assert!(err.is_err(), "only admins mark the podium matches");
+    f.program
+        .service("Service")
+        .set_podium_matches(2, 3)
+        .await
+        .unwrap();
+
+    let err = f.program.service("Service").set_podium_matches(3, 2).await;
+    assert!(err.is_err(), "a designated match is replaced only once it expired");
+
+    // Final drawn, Germany wins on penalties.
+    f.program
+        .service("Service")
+        .set_oracle_authorized(actor(ORACLE), true)
+        .await
+        .unwrap();
+    f.as_actor(ORACLE)
+        .service("Service")
+        .propose_result(2, Score { home: 1, away: 1 }, Some(PenaltyWinner::Away))
+        .await
+        .unwrap();
+    f.spend_blocks(CHALLENGE_WINDOW_BLOCKS + 1);
+    f.program
+        .service("Service")
+        .finalize_result(2)
+        .await
+        .unwrap();
+
+    let err = f.as_actor(STRANGER).service("Service").finalize_podium().await;
+    assert!(err.is_err(), "third-place match still pending");
+
+    propose_and_finalize(&f, 3, Score { home: 0, away: 2 }).await;
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .finalize_podium()
+        .await
+        .expect("podium finalization is permissionless");
+
+    // Champion (20) and runner-up (10) right; France, not Spain, took third.
+    let points = f
+        .program
+        .service("Service")
+        .query_user_points(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(points, 30);
+    let state = f.program.service("Service").query_state().query().unwrap();
+    assert!(state.podium_finalized);
+    assert_eq!(state.final_match_id, Some(2));
+}
+
+// ── Test 37: placeholder knockout match filled from its feeders ──────────────
+
+#[tokio::test]
+async fn placeholder_match_opens_once_feeders_finalize() {
+    let f = Fixture::new().await;
+
+    f.program
+        .service("Service")
+        .register_phase(KNOCKOUT_PHASE.to_string(), 0, u64::MAX, 4)
+        .await
+        .unwrap();
+    for (home, away) in [(HOME_TEAM, AWAY_TEAM), ("Spain", "France")] {
+        f.program
+            .service("Service")
+            .register_match(
+                KNOCKOUT_PHASE.to_string(),
+                home.to_string(),
+                away.to_string(),
+                KICK_OFF,
+            )
+            .await
+            .unwrap();
+    }
+    // Match 3: winner of match 1 vs winner of match 2.
+    f.program
+        .service("Service")
+        .register_placeholder_match(KNOCKOUT_PHASE.to_string(), 1, 2, KICK_OFF + 1_000)
+        .await
+        .unwrap();
+
+    let err = f
+        .program
+        .service("Service")
+        .register_placeholder_match(KNOCKOUT_PHASE.to_string(), 1, 3, KICK_OFF + 2_000)
+        .await;
+    assert!(err.is_err(), "a match feeds at most one placeholder");
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_bet(3, Score { home: 1, away: 0 }, None)
+        .with_value(BET_5_VARA)
+        .await;
+    assert!(err.is_err(), "no betting before the teams are known");
+
+    propose_and_finalize(&f, 1, Score { home: 2, away: 0 }).await;
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .place_bet(3, Score { home: 1, away: 0 }, None)
+        .with_value(BET_5_VARA)
+        .await;
+    assert!(err.is_err(), "away side still undecided");
+
+    propose_and_finalize(&f, 2, Score { home: 0, away: 1 }).await;
+
+    let m = f.program.service("Service").query_match(3).query().unwrap().unwrap();
+    assert_eq!((m.home.as_str(), m.away.as_str()), (HOME_TEAM, "France"));
+    assert_eq!(m.feeders, Some((1, 2)));
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(3, Score { home: 1, away: 0 }, None)
+        .with_value(BET_5_VARA)
+        .await
+        .expect("betting opens once both teams are known");
+}
+
+// ── Test 38: a joker multiplies the points of one bet per phase ──────────────
+
+#[tokio::test]
+async fn joker_doubles_points_once_per_phase() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            "Spain".to_string(),
+            "France".to_string(),
+            KICK_OFF,
+        )
+        .await
+        .unwrap();
+
+    let score = Score { home: 2, away: 1 };
+    for id in [match_id, 2] {
+        f.as_actor(USER1)
+            .service("Service")
+            .place_bet(id, score.clone(), None)
+            .with_value(BET_5_VARA)
+            .await
+            .unwrap();
+    }
+
+    let err = f.as_actor(USER2).service("Service").apply_joker(match_id).await;
+    assert!(err.is_err(), "a joker needs a bet on the match");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .apply_joker(match_id)
+        .await
+        .unwrap();
+    let err = f.as_actor(USER1).service("Service").apply_joker(2).await;
+    assert!(err.is_err(), "one joker per phase by default");
+
+    let remaining = f
+        .program
+        .service("Service")
+        .query_jokers_remaining(actor(USER1), GROUP_PHASE.to_string())
+        .query()
+        .unwrap();
+    assert_eq!(remaining, 0);
+
+    // Exact score: 3 points, doubled by the joker.
+    propose_and_finalize(&f, match_id, score).await;
+    let points = f
+        .program
+        .service("Service")
+        .query_user_points(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(points, 6);
+}
+
+// ── Test 39: streak and early-bird bonuses settle once the phase completes ───
+
+#[tokio::test]
+async fn phase_bonuses_follow_kick_off_order() {
+    let f = Fixture::new().await;
+    setup_phase_and_match(&f).await;
+    for (home, away, kick_off) in [
+        ("Spain", "France", KICK_OFF + 1_000),
+        ("Italy", "Japan", KICK_OFF + 2_000),
+    ] {
+        f.program
+            .service("Service")
+            .register_match(GROUP_PHASE.to_string(), home.to_string(), away.to_string(), kick_off)
+            .await
+            .unwrap();
+    }
+    f.program
+        .service("Service")
+        .set_streak_bonuses(vec![(2, 3)])
+        .await
+        .unwrap();
+    f.program
+        .service("Service")
+        .set_early_bird_bonus(86_400_000, 1)
+        .await
+        .unwrap();
+
+    for match_id in 1..=3 {
+        f.as_actor(USER1)
+            .service("Service")
+            .place_bet(match_id, Score { home: 1, away: 0 }, None)
+            .with_value(BET_5_VARA)
+            .await
+            .unwrap();
+    }
+
+    // Results land out of kick-off order: the third match first.
+    propose_and_finalize(&f, 3, Score { home: 0, away: 1 }).await;
+    propose_and_finalize(&f, 1, Score { home: 1, away: 0 }).await;
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .settle_phase_bonuses(GROUP_PHASE.to_string(), 10)
+        .await;
+    assert!(err.is_err(), "bonuses wait for the whole phase");
+
+    propose_and_finalize(&f, 2, Score { home: 2, away: 0 }).await;
+
+    // Matches 1 and 2 form a run of two (3 points); three early bets (1 point each).
+    let stats = f
+        .program
+        .service("Service")
+        .query_user_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!((stats.streak_bonus, stats.early_bird_bonus), (3, 3));
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .settle_phase_bonuses(GROUP_PHASE.to_string(), 10)
+        .await
+        .expect("phase bonus settlement is permissionless");
+
+    // Exact score 3 + correct outcome 1, plus 6 bonus points.
+    let points = f
+        .program
+        .service("Service")
+        .query_user_points(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!(points, 10);
+    let stats = f
+        .program
+        .service("Service")
+        .query_user_stats(actor(USER1))
+        .query()
+        .unwrap();
+    assert_eq!((stats.streak_bonus, stats.early_bird_bonus), (3, 3));
+}
+
+// ── Test 40: exact-score winners' stake counts more in the reward split ──────
+
+#[tokio::test]
+async fn exact_score_weight_tilts_reward_shares() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    f.program
+        .service("Service")
+        .set_exact_score_weight(3)
+        .await
+        .unwrap();
+
+    let score = Score { home: 2, away: 1 };
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, score.clone(), None)
+        .with_value(BET_5_VARA)
+        .await
+        .unwrap();
+    f.as_actor(USER2)
+        .service("Service")
+        .place_bet(match_id, Score { home: 1, away: 0 }, None)
+        .with_value(BET_5_VARA)
+        .await
+        .unwrap();
+
+    let err = f.program.service("Service").set_exact_score_weight(2).await;
+    assert!(err.is_err(), "weight is locked after the first bet");
+
+    propose_and_finalize(&f, match_id, score).await;
+
+    // Equal stakes, exact hit counted 3×: the pool splits 3:1.
+    let m = f
+        .program
+        .service("Service")
+        .query_match(match_id)
+        .query()
+        .unwrap()
+        .expect("match 1 should exist");
+    let exact = f
+        .program
+        .service("Service")
+        .query_wallet_claim_status(actor(USER1))
+        .query()
+        .unwrap();
+    let outcome_only = f
+        .program
+        .service("Service")
+        .query_wallet_claim_status(actor(USER2))
+        .query()
+        .unwrap();
+    assert_eq!(exact.amount_claimable, m.match_prize_pool * 3 / 4);
+    assert_eq!(outcome_only.amount_claimable, m.match_prize_pool / 4);
+
+    for user in [USER1, USER2] {
+        f.as_actor(user)
+            .service("Service")
+            .claim_match_reward(match_id)
+            .await
+            .unwrap();
+    }
+
+    // All weighted winner stake is claimed, so the sweep need not wait for the deadline.
+    f.as_actor(STRANGER)
+        .service("Service")
+        .sweep_match_dust_to_final_prize(match_id)
+        .await
+        .expect("sweep once every winner claimed");
+}
+
+// ── Test 41: settlement resumes across batches smaller than the bettor list ──
+
+#[tokio::test]
+async fn continue_settlement_spans_several_batches() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    let score = Score { home: 2, away: 1 };
+
+    for user in [USER1, USER2, STRANGER] {
+        f.as_actor(user)
+            .service("Service")
+            .place_bet(match_id, score.clone(), None)
+            .with_value(BET_5_VARA)
+            .await
+            .unwrap();
+    }
+
+    propose_and_finalize(&f, match_id, score).await;
+
+    for expected_cursor in 1..=3u32 {
+        f.as_actor(STRANGER)
+            .service("Service")
+            .continue_settlement(match_id, 1)
+            .await
+            .expect("each batch should credit one bettor");
+
+        let m = f
+            .program
+            .service("Service")
+            .query_match(match_id)
+            .query()
+            .unwrap()
+            .unwrap();
+        assert_eq!(m.settlement_cursor, expected_cursor);
+        assert_eq!(
+            m.points_settled,
+            expected_cursor == 3,
+            "points_settled flips only once the last bettor is credited"
+        );
+    }
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .continue_settlement(match_id, 1)
+        .await;
+    assert!(err.is_err(), "nothing left to settle after the last batch");
+
+    for user in [USER1, USER2, STRANGER] {
+        let pts = f
+            .program
+            .service("Service")
+            .query_user_points(actor(user))
+            .query()
+            .unwrap();
+        assert_eq!(pts, 3, "every bettor is credited exactly once");
+    }
+}
+
+// ── Test 42: unclaimed expiry refunds are swept after the claim deadline ─────
+
+#[tokio::test]
+async fn unclaimed_expired_refunds_swept_to_final_prize() {
+    let f = Fixture::new().await;
+
+    f.program
+        .service("Service")
+        .register_phase(GROUP_PHASE.to_string(), 0, u64::MAX, 1)
+        .await
+        .unwrap();
+    f.program
+        .service("Service")
+        .set_resolution_period(3_600_000)
+        .await
+        .unwrap();
+
+    let kick_off = f.env.system().block_timestamp() + 3_600_000;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            HOME_TEAM.to_string(),
+            AWAY_TEAM.to_string(),
+            kick_off,
+        )
+        .await
+        .unwrap();
+    let match_id = 1;
+
+    for user in [USER1, USER2] {
+        f.as_actor(user)
+            .service("Service")
+            .place_bet(match_id, Score { home: 1, away: 0 }, None)
+            .with_value(BET_10_VARA)
+            .await
+            .unwrap();
+    }
+
+    f.spend_blocks(2 * 1_200 + 1);
+    f.as_actor(STRANGER)
+        .service("Service")
+        .expire_match(match_id)
+        .await
+        .unwrap();
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_refund(match_id)
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .sweep_match_dust_to_final_prize(match_id)
+        .await;
+    assert!(err.is_err(), "USER2's refund is still claimable");
+
+    let before = f.program.service("Service").query_state().query().unwrap();
+    f.spend_blocks(CLAIM_DEADLINE_BLOCKS + 1);
+    f.as_actor(STRANGER)
+        .service("Service")
+        .sweep_match_dust_to_final_prize(match_id)
+        .await
+        .expect("unclaimed refunds are swept after the claim deadline");
+
+    let after = f.program.service("Service").query_state().query().unwrap();
+    assert_eq!(
+        after.final_prize_accumulated,
+        before.final_prize_accumulated + BET_10_VARA * 85 / 100
+    );
+
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .claim_refund(match_id)
+        .await;
+    assert!(err.is_err(), "refunds close once swept");
+}
+
+// ── Test 43: a match frozen by disputes expires and refunds the bonds ────────
+
+#[tokio::test]
+async fn disputed_match_expires_and_refunds_bonds() {
+    let f = Fixture::new().await;
+
+    f.program
+        .service("Service")
+        .register_phase(GROUP_PHASE.to_string(), 0, u64::MAX, 1)
+        .await
+        .unwrap();
+    f.program
+        .service("Service")
+        .set_resolution_period(3_600_000)
+        .await
+        .unwrap();
+
+    let kick_off = f.env.system().block_timestamp() + 3_600_000;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            HOME_TEAM.to_string(),
+            AWAY_TEAM.to_string(),
+            kick_off,
+        )
+        .await
+        .unwrap();
+    let match_id = 1;
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet(match_id, Score { home: 2, away: 0 }, None)
+        .with_value(BET_5_VARA)
+        .await
+        .unwrap();
+
+    propose(&f, match_id, Score { home: 0, away: 0 }).await;
+    f.as_actor(USER1)
+        .service("Service")
+        .dispute_result(match_id, Score { home: 2, away: 0 }, None)
+        .with_value(DISPUTE_BOND)
+        .await
+        .unwrap();
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .expire_match(match_id)
+        .await;
+    assert!(err.is_err(), "the resolution deadline has not passed yet");
+
+    f.spend_blocks(2 * 1_200 + 1);
+    f.as_actor(STRANGER)
+        .service("Service")
+        .expire_match(match_id)
+        .await
+        .expect("an unresolved dispute must not block expiry forever");
+
+    let m = f
+        .program
+        .service("Service")
+        .query_match(match_id)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert!(matches!(m.result, ResultStatus::Expired));
+    assert_eq!(m.open_disputes, 0);
+
+    let disputes = f
+        .program
+        .service("Service")
+        .query_disputes(match_id)
+        .query()
+        .unwrap();
+    assert_eq!(disputes[0].status, DisputeStatus::Upheld, "the bond is refunded");
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_refund(match_id)
+        .await
+        .expect("bettors are refunded as on any expired match");
+}
+
+// ── Test 44: token mode collects, pays out and parks failed transfers ────────
+
+#[tokio::test]
+async fn token_mode_parks_failed_transfers() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+    // The mock VFT refuses pulls from STRANGER and transfers to USER2.
+    let vft = f.deploy_mock_vft(STRANGER, USER2);
+    f.program
+        .service("Service")
+        .set_payment_token(Some(vft))
+        .await
+        .unwrap();
+    let score = Score { home: 1, away: 0 };
+
+    f.as_actor(USER1)
+        .service("Service")
+        .place_bet_with_token(match_id, score.clone(), None, BET_10_VARA)
+        .await
+        .expect("stake is pulled with TransferFrom");
+
+    let err = f
+        .as_actor(STRANGER)
+        .service("Service")
+        .place_bet_with_token(match_id, score.clone(), None, BET_10_VARA)
+        .await;
+    assert!(err.is_err(), "a failed TransferFrom places no bet");
+
+    // Two bets from USER2 in one block: both pass the first check, the second fails
+    // re-validation and its refund transfer fails, so the refund is parked.
+    let env = f
+        .env
+        .clone()
+        .with_actor_id(actor(USER2))
+        .with_block_run_mode(BlockRunMode::Manual);
+    let user2 = Actor::<BolaoProgram, GtestEnv>::new(env, f.program.id());
+    for _ in 0..2 {
+        user2
+            .service("Service")
+            .place_bet_with_token(match_id, score.clone(), None, BET_10_VARA)
+            .send_one_way()
+            .unwrap();
+    }
+    f.spend_blocks(3);
+
+    let m = f
+        .program
+        .service("Service")
+        .query_match(match_id)
+        .query()
+        .unwrap()
+        .unwrap();
+    assert_eq!(m.participants, vec![actor(USER1), actor(USER2)]);
+    let solvency = f.program.service("Service").query_solvency().query().unwrap();
+    assert_eq!(solvency.other_liabilities, BET_10_VARA, "the refund is parked");
+
+    let err = f
+        .as_actor(USER1)
+        .service("Service")
+        .retry_failed_payout()
+        .await;
+    assert!(err.is_err(), "USER1 has nothing parked");
+    f.as_actor(USER2)
+        .service("Service")
+        .retry_failed_payout()
+        .await
+        .expect("a failed retry parks the refund again");
+
+    propose_and_finalize(&f, match_id, score).await;
+
+    f.as_actor(USER1)
+        .service("Service")
+        .claim_match_reward(match_id)
+        .await
+        .expect("reward is paid with Transfer");
+
+    let owed = f
+        .program
+        .service("Service")
+        .query_wallet_claim_status(actor(USER2))
+        .query()
+        .unwrap()
+        .amount_claimable;
+    f.as_actor(USER2)
+        .service("Service")
+        .claim_match_reward(match_id)
+        .await
+        .expect("a failed payout keeps the claim and parks the amount");
+
+    let err = f
+        .as_actor(USER2)
+        .service("Service")
+        .claim_match_reward(match_id)
+        .await;
+    assert!(err.is_err(), "the claim stays committed");
+    let solvency = f.program.service("Service").query_solvency().query().unwrap();
+    assert_eq!(solvency.other_liabilities, BET_10_VARA + owed);
+    assert!(solvency.payment_token.is_some(), "token-mode report is flagged");
+    assert_eq!(solvency.balance, 0);
+    assert_eq!(solvency.deficit, 0, "native balance is not compared in token mode");
+}
+
+// ── Test 45: both-teams-to-score and Asian-handicap markets ──────────────────
+
+#[tokio::test]
+async fn btts_and_handicap_markets_settle() {
+    let f = Fixture::new().await;
+    let match_id = setup_phase_and_match(&f).await;
+
+    // Market 0: both teams to score; market 1: home -0.5; market 2: home -1 (pushes on a
+    // one-goal home win).
+    let markets = [
+        (MarketKind::BothTeamsToScore, 0),
+        (MarketKind::AsianHandicap, -50),
+        (MarketKind::AsianHandicap, -100),
+    ];
+    for (kind, line) in markets {
+        f.program
+            .service("Service")
+            .create_market(match_id, kind, MarketParams { line })
+            .await
+            .unwrap();
+    }
+
+    let bets = [
+        (USER1, 0, MarketSelection::Yes),
+        (USER2, 0, MarketSelection::No),
+        (USER1, 1, MarketSelection::Home),
+        (USER2, 1, MarketSelection::Away),
+        (USER2, 2, MarketSelection::Away),
+    ];
+    for (user, market_id, selection) in bets {
+        f.as_actor(user)
+            .service("Service")
+            .place_market_bet(market_id, selection, BET_10_VARA)
+            .with_value(BET_10_VARA)
+            .await
+            .unwrap();
+    }
+
+    propose_and_finalize(&f, match_id, Score { home: 1, away: 0 }).await;
+
+    for market_id in [0, 1, 2] {
+        f.as_actor(STRANGER)
+            .service("Service")
+            .settle_market(market_id)
+            .await
+            .unwrap();
+    }
+
+    let markets = f
+        .program
+        .service("Service")
+        .query_markets_by_match(match_id)
+        .query()
+        .unwrap();
+    let market = |id: u64| markets.iter().find(|m| m.market_id == id).unwrap().clone();
+    assert_eq!(market(0).winning_selection, Some(MarketSelection::No));
+    assert_eq!(market(1).winning_selection, Some(MarketSelection::Home));
+    assert_eq!(market(2).status, MarketStatus::Void);
+
+    let err = f.as_actor(USER1).service("Service").claim_market_reward(0).await;
+    assert!(err.is_err(), "only the away side failed to score");
+    let err = f.as_actor(USER2).service("Service").claim_market_reward(1).await;
+    assert!(err.is_err(), "home covered the half-goal handicap");
+
+    let claims = [(USER2, 0), (USER1, 1), (USER2, 2)];
+    for (user, market_id) in claims {
+        f.as_actor(user)
+            .service("Service")
+            .claim_market_reward(market_id)
+            .await
+            .unwrap();
+    }
+
+    // Sole winners take both 85% pool stakes; the push refunds the pool stake.
+    let paid = |market_id: u64, user: u64| {
+        f.program
+            .service("Service")
+            .query_market_bet(market_id, actor(user))
+            .query()
+            .unwrap()
+            .unwrap()
+            .paid_out
+    };
+    assert_eq!(paid(0, USER2), 2 * BET_10_VARA * 85 / 100);
+    assert_eq!(paid(1, USER1), 2 * BET_10_VARA * 85 / 100);
+    assert_eq!(paid(2, USER2), BET_10_VARA * 85 / 100);
+}
+
+// ── Test 46: survivor game finishes after a multi-survivor last round ────────
+
+#[tokio::test]
+async fn survivor_finishes_once_pending_matches_settle() {
+    let f = Fixture::new().await;
+    let first = setup_phase_and_match(&f).await;
+    f.program
+        .service("Service")
+        .register_match(
+            GROUP_PHASE.to_string(),
+            AWAY_TEAM.to_string(),
+            HOME_TEAM.to_string(),
+            KICK_OFF + 1_000,
+        )
+        .await
+        .unwrap();
+    let unrelated = 2;
+
+    f.program
+        .service("Service")
+        .register_survivor_round(vec![first])
+        .await
+        .unwrap();
+    for user in [USER1, USER2] {
+        f.as_actor(user)
+            .service("Service")
+            .join_survivor()
+            .with_value(MIN_BET)
+            .await
+            .unwrap();
+        f.as_actor(user)
+            .service("Service")
+            .survivor_pick(0, HOME_TEAM.to_string())
+            .await
+            .unwrap();
+    }
+
+    propose_and_finalize(&f, first, Score { home: 1, away: 0 }).await;
+    f.as_actor(STRANGER)
+        .service("Service")
+        .settle_survivor_round(0, 10)
+        .await
+        .unwrap();
+
+    // Both survived the last round, but the unrelated match is still unresolved.
+    let status = f.program.service("Service").query_survivor_status().query().unwrap();
+    assert_eq!(status.alive, 2);
+    assert!(!status.finished);
+
+    let err = f.as_actor(STRANGER).service("Service").settle_survivor_round(0, 10).await;
+    assert!(err.is_err(), "the round is already settled");
+    let err = f.as_actor(STRANGER).service("Service").finish_survivor().await;
+    assert!(err.is_err(), "finishing waits for every match");
+
+    propose_and_finalize(&f, unrelated, Score { home: 0, away: 0 }).await;
+
+    f.as_actor(STRANGER)
+        .service("Service")
+        .finish_survivor()
+        .await
+        .expect("finishing the survivor game is permissionless");
+
+    let status = f.program.service("Service").query_survivor_status().query().unwrap();
+    assert!(status.finished);
+    assert_eq!(status.winners, 2);
+
+    for user in [USER1, USER2] {
+        f.as_actor(user)
+            .service("Service")
+            .claim_survivor_prize()
+            .await
+            .unwrap();
+    }
+    let stats = f
+        .program
+        .service("Service")
+        .query_user_stats(actor(USER2))
+        .query()
+        .unwrap();
+    assert_eq!(stats.total_won, MIN_BET * 85 / 100);
+}
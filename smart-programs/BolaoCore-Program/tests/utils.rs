@@ -2,6 +2,7 @@ pub const ONE_VARA: u128 = 1_000_000_000_000;
 pub const MIN_BET: u128 = 3 * ONE_VARA; // mirrors MIN_BET_PLANCK in constants.rs
 pub const BET_5_VARA: u128 = 5 * ONE_VARA;
 pub const BET_10_VARA: u128 = 10 * ONE_VARA;
+pub const DISPUTE_BOND: u128 = 10 * ONE_VARA; // mirrors DISPUTE_BOND_PLANCK in constants.rs
 
 /// Far-future kick-off time. gtest uses the real Unix timestamp in milliseconds
 /// (currently ~1.75 × 10¹²), so KICK_OFF must exceed that. Using year ~2286.
@@ -13,9 +14,9 @@ pub const HOME_TEAM: &str = "Brazil";
 pub const AWAY_TEAM: &str = "Germany";
 
 /// Blocks to advance to expire the 24h optimistic challenge window.
-/// gtest runs at 1 block = 1 000 ms, so 24 h = 86 400 blocks.
-pub const CHALLENGE_WINDOW_BLOCKS: u32 = 86_400;
+/// gtest runs at 1 block = 3 000 ms, so 24 h = 28 800 blocks.
+pub const CHALLENGE_WINDOW_BLOCKS: u32 = 28_800;
 
 /// Blocks to advance to expire the 72h claim deadline.
-/// 72 h = 259 200 blocks at 1 block/s.
-pub const CLAIM_DEADLINE_BLOCKS: u32 = 259_200;
+/// 72 h = 86 400 blocks at 3 s per block.
+pub const CLAIM_DEADLINE_BLOCKS: u32 = 86_400;